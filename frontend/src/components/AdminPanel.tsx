@@ -52,7 +52,7 @@ export function AdminPanel() {
       const tx = await service.createSession(
         sessionName,
         sessionDescription,
-        creditsPerVoter, // uint8 (max 255)
+        BigInt(creditsPerVoter), // uint256
         BigInt(durationHours * 3600), // uint64 - duration in seconds
         [] // Empty proposals array - proposals will be created separately
       );
@@ -149,7 +149,6 @@ export function AdminPanel() {
                   <Input
                     type="number"
                     min="1"
-                    max="255"
                     value={creditsPerVoter}
                     onChange={(e) =>
                       setCreditsPerVoter(parseInt(e.target.value) || 100)
@@ -20,6 +20,7 @@ export function VoterPanel() {
   const { account, signer } = useWallet();
   const [email, setEmail] = useState("");
   const [submitting, setSubmitting] = useState(false);
+  const [identitySalt, setIdentitySalt] = useState<string | null>(null);
   const [voterInfo, setVoterInfo] = useState<{
     address: string;
     isRegistered: string;
@@ -53,8 +54,10 @@ export function VoterPanel() {
       setSubmitting(true);
       const service = new QuadraticVotingService(signer);
 
+      // The salt is the only way to rebuild the identity commitment, so it is shown to the voter below
+      const salt = QuadraticVotingService.newIdentitySalt();
       console.log("Registering voter with email:", email);
-      const tx = await service.registerVoter(email);
+      const tx = await service.registerVoter(email, salt);
 
       toast.success("Registration transaction sent!");
       console.log("Transaction hash:", tx.hash);
@@ -64,6 +67,8 @@ export function VoterPanel() {
         `Voter registration successful! Block: ${receipt.blockNumber}`
       );
 
+      setIdentitySalt(salt);
+
       // Refresh voter info after successful registration
       await fetchVoterInfo();
       setEmail("");
@@ -193,6 +198,17 @@ export function VoterPanel() {
             </div>
           </div>
 
+          {identitySalt && (
+            <div className="p-4 border rounded-lg space-y-1">
+              <h3 className="font-medium">Identity Salt</h3>
+              <p className="text-sm font-mono break-all">{identitySalt}</p>
+              <p className="text-sm text-muted-foreground">
+                Save this value. Together with your email it is the only way
+                to prove the identity you registered.
+              </p>
+            </div>
+          )}
+
           {/* Voter Registration */}
           <div className="space-y-3">
             <h3 className="text-lg font-medium">Register to Vote</h3>
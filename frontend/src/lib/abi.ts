@@ -4,7 +4,10 @@ export const QUADRATIC_VOTING_ABI = [
   {
     type: "function",
     name: "registerVoter",
-    inputs: [{ name: "email", type: "string" }],
+    inputs: [
+      { name: "identity", type: "bytes32" },
+      { name: "attestation", type: "bytes" },
+    ],
     outputs: [],
     stateMutability: "nonpayable",
   },
@@ -16,8 +19,19 @@ export const QUADRATIC_VOTING_ABI = [
     inputs: [
       { name: "name", type: "string" },
       { name: "description", type: "string" },
-      { name: "credits_per_voter", type: "uint8" },
+      { name: "credits_per_voter", type: "uint256" },
+      { name: "start_time", type: "uint64" },
       { name: "duration_seconds", type: "uint64" },
+      {
+        name: "initial_proposals",
+        type: "tuple[]",
+        components: [
+          { name: "", type: "string" },
+          { name: "", type: "string" },
+        ],
+      },
+      { name: "eligibility_root", type: "bytes32" },
+      { name: "winners_count", type: "uint16" },
     ],
     outputs: [],
     stateMutability: "nonpayable",
@@ -29,10 +43,52 @@ export const QUADRATIC_VOTING_ABI = [
     name: "getSessionResults",
     inputs: [{ name: "session_id", type: "uint64" }],
     outputs: [
-      { name: "", type: "uint8" },
-      { name: "", type: "uint8" },
+      { name: "", type: "uint16" },
+      { name: "", type: "uint16" },
       { name: "", type: "uint64" },
       { name: "", type: "uint64" },
+      { name: "", type: "uint8" },
+    ],
+    stateMutability: "view",
+  },
+
+  // Proposals funded by a budget session and the budget left over
+  {
+    type: "function",
+    name: "getBudgetAllocation",
+    inputs: [{ name: "session_id", type: "uint64" }],
+    outputs: [
+      { name: "", type: "bool" },
+      { name: "", type: "uint16[]" },
+      { name: "", type: "uint256" },
+    ],
+    stateMutability: "view",
+  },
+
+  // Proposals selected by a finished session
+  {
+    type: "function",
+    name: "getWinners",
+    inputs: [{ name: "session_id", type: "uint64" }],
+    outputs: [{ name: "", type: "uint16[]" }],
+    stateMutability: "view",
+  },
+
+  // Ranked proposals with tie groups
+  {
+    type: "function",
+    name: "getLeaderboard",
+    inputs: [{ name: "session_id", type: "uint64" }],
+    outputs: [
+      {
+        name: "",
+        type: "tuple[]",
+        components: [
+          { name: "proposal_id", type: "uint16" },
+          { name: "net_score", type: "int128" },
+          { name: "rank", type: "uint16" },
+        ],
+      },
     ],
     stateMutability: "view",
   },
@@ -41,22 +97,44 @@ export const QUADRATIC_VOTING_ABI = [
   {
     type: "function",
     name: "getSessionProposals",
-    inputs: [{ name: "session_id", type: "uint64" }],
+    inputs: [
+      { name: "session_id", type: "uint64" },
+      { name: "offset", type: "uint16" },
+      { name: "limit", type: "uint16" },
+    ],
     outputs: [
       {
         name: "",
         type: "tuple[]",
         components: [
-          { name: "", type: "uint8" },
+          { name: "", type: "uint16" },
           { name: "", type: "string" },
           { name: "", type: "string" },
           { name: "", type: "uint64" },
+          { name: "", type: "uint64" },
+          { name: "", type: "int128" },
         ],
       },
     ],
     stateMutability: "view",
   },
 
+  // Get a voter's credit ledger for a session
+  {
+    type: "function",
+    name: "getVoterSessionCredits",
+    inputs: [
+      { name: "session_id", type: "uint64" },
+      { name: "voter", type: "address" },
+    ],
+    outputs: [
+      { name: "", type: "uint256" },
+      { name: "", type: "uint256" },
+      { name: "", type: "uint256" },
+    ],
+    stateMutability: "view",
+  },
+
   // Error types from your generated ABI
   {
     type: "error",
@@ -93,4 +171,9 @@ export const QUADRATIC_VOTING_ABI = [
     name: "InvalidProposalCount",
     inputs: [],
   },
+  {
+    type: "error",
+    name: "InvalidProposal",
+    inputs: [],
+  },
 ];
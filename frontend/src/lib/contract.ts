@@ -7,6 +7,8 @@ interface ProposalData {
   title: string;
   description: string;
   voteCount: bigint;
+  againstCount?: bigint;
+  netScore?: bigint;
 }
 
 interface SessionData {
@@ -104,9 +106,21 @@ export class QuadraticVotingService {
   }
 
   // Voter functions
-  async registerVoter(email: string) {
-    console.log("🚀 Calling contract.registerVoter with email:", email);
-    return await this.contract.registerVoter(email);
+  // Only keccak256(abi.encode(email, salt)) is sent; the caller must keep the salt to prove the identity later
+  static newIdentitySalt() {
+    return ethers.hexlify(ethers.randomBytes(32));
+  }
+
+  static identityCommitment(email: string, salt: string) {
+    return ethers.keccak256(
+      ethers.AbiCoder.defaultAbiCoder().encode(["string", "bytes32"], [email, salt])
+    );
+  }
+
+  async registerVoter(email: string, salt: string, attestation: string = "0x") {
+    const identity = QuadraticVotingService.identityCommitment(email, salt);
+    console.log("🚀 Calling contract.registerVoter with identity:", identity);
+    return await this.contract.registerVoter(identity, attestation);
   }
 
   // Note: getVoter function doesn't exist in the actual contract
@@ -116,9 +130,12 @@ export class QuadraticVotingService {
   async createSession(
     name: string,
     description: string,
-    creditsPerVoter: number, // uint8 in your contract
+    creditsPerVoter: bigint, // uint256 in your contract
     durationSeconds: bigint, // uint64 in your contract
-    initialProposals: Array<{ title: string; description: string }> = []
+    initialProposals: Array<{ title: string; description: string }> = [],
+    startTime: bigint = BigInt(0), // unix timestamp, 0 starts immediately
+    eligibilityRoot: string = ethers.ZeroHash, // Merkle root of eligible voters, zero for none
+    winnersCount: number = 1 // how many top proposals the session selects
   ) {
     console.log("🚀 Calling contract.createSession with params:", {
       name,
@@ -138,8 +155,11 @@ export class QuadraticVotingService {
       name,
       description,
       creditsPerVoter,
+      startTime,
       durationSeconds,
-      proposalTuples
+      proposalTuples,
+      eligibilityRoot,
+      winnersCount
     );
   }
 
@@ -158,13 +178,15 @@ export class QuadraticVotingService {
     return QuadraticVotingService.formatProposalData(result);
   }
 
-  async getSessionProposals(sessionId: bigint) {
-    const results = await this.contract.get_session_proposals(sessionId);
-    return results.map((result: [bigint, string, string, bigint]) => ({
+  async getSessionProposals(sessionId: bigint, offset = 0, limit = 100) {
+    const results = await this.contract.get_session_proposals(sessionId, offset, limit);
+    return results.map((result: [bigint, string, string, bigint, bigint, bigint]) => ({
       id: result[0],
       title: result[1],
       description: result[2],
       voteCount: result[3],
+      againstCount: result[4],
+      netScore: result[5],
     }));
   }
 
@@ -199,10 +221,13 @@ export class QuadraticVotingService {
   }
 
   async getVoterSessionCredits(sessionId: bigint, voter: string) {
-    // Note: This method doesn't exist in the current contract ABI
-    // For demo purposes, return default values
-    console.warn("getVoterSessionCredits method not implemented in contract - returning default values");
-    return BigInt(100); // Default credits for the session
+    const [allocated, spent, remaining] =
+      await this.contract.getVoterSessionCredits(sessionId, voter);
+    return {
+      allocated: BigInt(allocated),
+      spent: BigInt(spent),
+      remaining: BigInt(remaining),
+    };
   }
 
   async getVoterCredits(account: string) {
@@ -0,0 +1,106 @@
+//!
+//! EIP-712 typed-data ballots for gasless voting through `vote_by_sig`.
+//!
+//! Voters sign a `Ballot` off-chain under the `QuadraticVoting` version "1" domain of the
+//! deployed contract, and anyone can relay the signature on-chain. Registrars sign a
+//! `Registration` to attest an identity commitment submitted with `register_voter`.
+//!
+
+use alloc::vec::Vec;
+use stylus_sdk::{
+    alloy_primitives::{Address, B256, U16, U256, U64},
+    alloy_sol_types::{sol, Eip712Domain, SolStruct},
+};
+
+sol! {
+    struct Ballot {
+        uint64 sessionId;
+        uint16[] proposalIds;
+        uint64[] voteCounts;
+        bool[] against;
+        address voter;
+        uint256 nonce;
+        uint256 deadline;
+    }
+
+    struct Registration {
+        address voter;
+        bytes32 identity;
+    }
+}
+
+/// Length of an `r || s || v` secp256k1 signature
+pub const SIGNATURE_LENGTH: usize = 65;
+
+/// Signing domain of a deployed contract
+pub fn domain(chain_id: u64, verifying_contract: Address) -> Eip712Domain {
+    Eip712Domain::new(
+        Some("QuadraticVoting".into()),
+        Some("1".into()),
+        Some(U256::from(chain_id)),
+        Some(verifying_contract),
+        None,
+    )
+}
+
+/// EIP-712 digest a voter signs to authorize `vote_by_sig`
+#[allow(clippy::too_many_arguments)]
+pub fn ballot_digest(
+    chain_id: u64,
+    verifying_contract: Address,
+    session_id: U64,
+    proposal_ids: &[U16],
+    vote_counts: &[U64],
+    against: &[bool],
+    voter: Address,
+    nonce: U256,
+    deadline: U256,
+) -> B256 {
+    let ballot = Ballot {
+        sessionId: session_id.to::<u64>(),
+        proposalIds: proposal_ids.iter().map(|id| id.to::<u16>()).collect::<Vec<_>>(),
+        voteCounts: vote_counts.iter().map(|count| count.to::<u64>()).collect::<Vec<_>>(),
+        against: against.to_vec(),
+        voter,
+        nonce,
+        deadline,
+    };
+    ballot.eip712_signing_hash(&domain(chain_id, verifying_contract))
+}
+
+/// EIP-712 digest a registrar signs to attest that `identity` belongs to `voter`
+pub fn registration_digest(
+    chain_id: u64,
+    verifying_contract: Address,
+    voter: Address,
+    identity: B256,
+) -> B256 {
+    let registration = Registration { voter, identity };
+    registration.eip712_signing_hash(&domain(chain_id, verifying_contract))
+}
+
+/// Off-chain signing helpers for relayers, scripts and tests. Not compiled into the contract
+#[cfg(not(target_arch = "wasm32"))]
+pub mod signer {
+    use super::SIGNATURE_LENGTH;
+    use k256::ecdsa::SigningKey;
+    use stylus_sdk::alloy_primitives::{keccak256, Address, B256};
+
+    /// Ethereum address controlled by `key`
+    pub fn address_of(key: &SigningKey) -> Address {
+        let point = key.verifying_key().to_encoded_point(false);
+        Address::from_slice(&keccak256(&point.as_bytes()[1..])[12..])
+    }
+
+    /// Sign a digest, returning `r || s || v` with a low `s` and `v` in {27, 28}
+    pub fn sign_digest(key: &SigningKey, digest: B256) -> [u8; SIGNATURE_LENGTH] {
+        let (signature, recovery_id) = key
+            .sign_prehash_recoverable(digest.as_slice())
+            .expect("signing a 32-byte prehash cannot fail");
+
+        let mut out = [0u8; SIGNATURE_LENGTH];
+        out[..64].copy_from_slice(&signature.to_bytes());
+        out[64] = 27 + recovery_id.to_byte();
+        out
+    }
+}
@@ -0,0 +1,41 @@
+//!
+//! Quadratic funding (CLR) math for distributing a matching pool across proposals.
+//!
+//! A proposal with contributions cᵢ ideally receives (Σ√cᵢ)² − Σcᵢ in matching funds. Ideal
+//! amounts are then scaled so the whole pool is shared in proportion to them.
+//!
+
+use stylus_sdk::alloy_primitives::{U256, U512};
+
+/// Integer square root, rounded down
+pub fn isqrt(value: U256) -> U256 {
+    if value < U256::from(2) {
+        return value;
+    }
+
+    // Newton's method from an initial guess above the root
+    let mut x = U256::from(1) << value.bit_len().div_ceil(2);
+    loop {
+        let y = (x + value / x) >> 1;
+        if y >= x {
+            return x;
+        }
+        x = y;
+    }
+}
+
+/// Unscaled CLR match for a proposal: (Σ√cᵢ)² − Σcᵢ, or `None` on overflow
+pub fn ideal_match(sqrt_sum: U256, contributions: U256) -> Option<U256> {
+    // Square roots are rounded down, so a single contributor can land just below Σcᵢ
+    Some(sqrt_sum.checked_mul(sqrt_sum)?.saturating_sub(contributions))
+}
+
+/// Share of `pool` for a proposal whose ideal match is `ideal` out of `total_ideal`, rounded down
+pub fn scaled_match(pool: U256, ideal: U256, total_ideal: U256) -> U256 {
+    if total_ideal.is_zero() {
+        return U256::ZERO;
+    }
+    let share: U512 = pool.widening_mul(ideal) / U512::from(total_ideal);
+    // ideal <= total_ideal, so the share never exceeds the pool
+    share.to()
+}
@@ -9,32 +9,167 @@
 
 extern crate alloc;
 
+pub mod eip712;
+pub mod funding;
+pub mod merkle;
+#[cfg(feature = "quadratic-funding")]
+mod quadratic_funding;
+#[cfg(feature = "signed-ballots")]
+mod signed_ballots;
+#[cfg(feature = "token-credits")]
+mod token_credits;
+
+#[cfg(feature = "quadratic-funding")]
+pub use quadratic_funding::QuadraticFunding;
+#[cfg(feature = "signed-ballots")]
+pub use signed_ballots::SignedBallots;
+#[cfg(feature = "token-credits")]
+pub use token_credits::TokenCredits;
+
 use alloc::{string::String, vec::Vec, vec};
 use stylus_sdk::{
-    alloy_primitives::{Address, U256, U64, U8, B256},
-    alloy_sol_types::sol,
+    abi::Bytes,
+    alloy_primitives::{address, b256, Address, I128, I64, U16, U256, U32, U64, U8, B256},
+    alloy_sol_types::{sol, sol_data, SolType},
+    crypto::keccak,
     prelude::*,
 };
 
+/// (name, description, start_time, end_time, credits_per_voter, active, creator, proposal_count, winners_count)
+pub type SessionInfo = (String, String, U256, U256, U256, bool, Address, U16, U16);
+
+/// (proposal_id, title, description, votes_for, votes_against, net_score), see get_session_proposals
+pub type ProposalInfo = (U16, String, String, U64, U64, I128);
+
+/// (session_id, proposal_ids, vote_counts, against, voter, nonce, deadline, signature), see vote_by_sig
+pub type SignedBallot = (U64, Vec<U16>, Vec<U64>, Vec<bool>, Address, U256, U256, Bytes);
+
+// Access control roles. ADMIN_ROLE manages every other role and is only
+// transferred through the two-step transfer_admin / accept_admin flow
+pub const ADMIN_ROLE: B256 = B256::ZERO;
+/// keccak256("SESSION_CREATOR_ROLE")
+pub const SESSION_CREATOR_ROLE: B256 =
+    b256!("5437a4b3bdc20414614c7343053aad4b219cf8d2f00940a56ccb2cdaa6e15793");
+/// keccak256("VOTER_REGISTRAR_ROLE")
+pub const VOTER_REGISTRAR_ROLE: B256 =
+    b256!("9f4afe075076c8d761788c418fef49796bfe243c483d034558e0f388cc918452");
+/// keccak256("PAUSER_ROLE")
+pub const PAUSER_ROLE: B256 =
+    b256!("65d7a28e3265b37a6474929f336521b332c1681b933f6cb9f3376673440d862a");
+
+// Voter registration modes
+/// Anyone can self-register with register_voter
+pub const REGISTRATION_OPEN: u8 = 0;
+/// register_voter only files a request that a registrar must approve
+pub const REGISTRATION_APPROVAL: u8 = 1;
+/// Self-registration is disabled; registrars add voters with register_voters
+pub const REGISTRATION_ALLOWLIST: u8 = 2;
+
+// Tie-break policies deciding a session's winner among proposals sharing the top vote count
+/// Ties go to the lowest proposal ID
+pub const TIE_BREAK_LOWEST_ID: u8 = 0;
+/// Ties go to the proposal that reached the winning count first
+pub const TIE_BREAK_EARLIEST: u8 = 1;
+/// The session creator picks among the tied proposals with resolve_tie once voting is over
+pub const TIE_BREAK_CREATOR: u8 = 2;
+/// A tie means the session has no winner
+pub const TIE_BREAK_NONE: u8 = 3;
+
+// Session outcomes reported by get_session_results and frozen by finalize_session
+/// Quorum was reached and a winner met the vote threshold
+pub const OUTCOME_PASSED: u8 = 0;
+/// Too few distinct voters took part, or too few credits were spent
+pub const OUTCOME_FAILED_QUORUM: u8 = 1;
+/// No proposal has a positive net score, or the top score fell short of the vote threshold
+pub const OUTCOME_NO_WINNER: u8 = 2;
+/// Proposals tied for the most votes and the tie-break policy left the tie unsettled
+pub const OUTCOME_TIED: u8 = 3;
+
 // Constants to limit loop iterations and prevent contract bloat
-const MAX_PROPOSALS_PER_SESSION: u8 = 10;
-
-// ------------------------------------------------------------
-// Helper conversions between Rust `&str` and fixed-length `B256`
-// ------------------------------------------------------------
-fn to_b256(s: &str) -> B256 {
-    let mut bytes = [0u8; 32];
-    let slice = s.as_bytes();
-    let len = core::cmp::min(slice.len(), 32);
-    bytes[..len].copy_from_slice(&slice[..len]);
-    B256::from(bytes)
+const MAX_PROPOSALS_PER_SESSION: u16 = 1000;
+/// Longest session name or proposal title, in bytes
+const MAX_TITLE_LENGTH: usize = 256;
+/// Longest session or proposal description, in bytes. Longer documents belong off-chain,
+/// e.g. an IPFS URI in the description
+const MAX_DESCRIPTION_LENGTH: usize = 1024;
+const MAX_DELEGATION_DEPTH: usize = 8;
+/// ecrecover precompile
+const ECRECOVER: Address = address!("0000000000000000000000000000000000000001");
+/// Upper bound for `s` in a canonical secp256k1 signature (secp256k1n / 2)
+const SECP256K1_HALF_ORDER: U256 = U256::from_limbs([
+    0xdfe92f46681b20a0, 0x5d576e7357a4501d, 0xffffffffffffffff, 0x7fffffffffffffff,
+]);
+
+/// @dev Rejects session or proposal text that exceeds the stored length limits
+/// @param title Session name or proposal title
+/// @param description Session or proposal description
+fn check_content(title: &str, description: &str) -> Result<(), QuadraticVotingError> {
+    if title.len() > MAX_TITLE_LENGTH || description.len() > MAX_DESCRIPTION_LENGTH {
+        return Err(QuadraticVotingError::ContentTooLong(ContentTooLong {}));
+    }
+    Ok(())
+}
+
+/// @dev Validates a batch of (title, description) proposals before any of them is stored
+/// @param proposals Array of (title, description) tuples
+fn check_proposals(proposals: &[(String, String)]) -> Result<(), QuadraticVotingError> {
+    if proposals.len() > MAX_PROPOSALS_PER_SESSION as usize {
+        return Err(QuadraticVotingError::InvalidProposalCount(InvalidProposalCount {}));
+    }
+    for (title, description) in proposals {
+        check_content(title, description)?;
+    }
+    Ok(())
+}
+
+/// Commitment a voter submits with commit_vote in a commit-reveal session:
+/// `keccak256(abi.encode(session_id, voter, proposal_ids, vote_counts, against, salt))`
+pub fn vote_commitment(
+    session_id: U64,
+    voter: Address,
+    proposal_ids: &[U16],
+    vote_counts: &[U64],
+    against: &[bool],
+    salt: B256,
+) -> B256 {
+    type Preimage = (
+        sol_data::Uint<64>,
+        sol_data::Address,
+        sol_data::Array<sol_data::Uint<16>>,
+        sol_data::Array<sol_data::Uint<64>>,
+        sol_data::Array<sol_data::Bool>,
+        sol_data::FixedBytes<32>,
+    );
+    let encoded = Preimage::abi_encode_params(&(
+        session_id.to::<u64>(),
+        voter,
+        proposal_ids.iter().map(|id| id.to::<u16>()).collect::<Vec<_>>(),
+        vote_counts.iter().map(|count| count.to::<u64>()).collect::<Vec<_>>(),
+        against.to_vec(),
+        salt,
+    ));
+    keccak(encoded)
+}
+
+/// Identity commitment a voter registers with instead of their email:
+/// `keccak256(abi.encode(email, salt))`. The salt stays with the voter (or the registrar that
+/// attests to it), so the email cannot be recovered by hashing candidate addresses
+pub fn identity_commitment(email: &str, salt: B256) -> B256 {
+    type Preimage = (sol_data::String, sol_data::FixedBytes<32>);
+    keccak(Preimage::abi_encode_params(&(email, salt)))
+}
+
+/// Score a proposal leads with: its net score when positive, otherwise 0. Only proposals
+/// with more support than opposition can win, and a positive net never exceeds `support`
+fn lead_score(support: U64, against: U64) -> U64 {
+    support.saturating_sub(against)
 }
 
-fn from_b256(b: B256) -> String {
-    let arr: [u8; 32] = b.into();
-    // Find the first 0 byte (padding) or fall back to full length
-    let len = arr.iter().position(|&c| c == 0).unwrap_or(32);
-    String::from_utf8_lossy(&arr[..len]).into_owned()
+/// Quadratic price of casting `votes` on a single proposal. A `U64` intensity
+/// squared always fits in 128 bits, so this cannot overflow.
+fn quadratic_cost(votes: U64) -> U256 {
+    let votes = U256::from(votes);
+    votes * votes
 }
 
 sol! {
@@ -50,9 +185,76 @@ sol! {
     error Unauthorized();
     #[derive(Debug)]
     error InvalidProposalCount();
-    event SessionCreated(uint64 indexed id, address indexed creator, bytes32 name);
-    event VoterRegistered(address indexed voter, bytes32 email);
-    event VoteCast(uint64 indexed session_id, address indexed voter, uint64 total_credits_spent);
+    #[derive(Debug)]
+    error InvalidProposal();
+    #[derive(Debug)]
+    error InvalidMode();
+    #[derive(Debug)]
+    error InvalidProof();
+    #[derive(Debug)]
+    error InvalidCommitment();
+    #[derive(Debug)]
+    error InvalidDelegate();
+    #[derive(Debug)]
+    error DelegationCycle();
+    #[derive(Debug)]
+    error InvalidSignature();
+    #[derive(Debug)]
+    error SignatureExpired();
+    #[derive(Debug)]
+    error InvalidNonce();
+    #[derive(Debug)]
+    error TokenTransferFailed();
+    #[derive(Debug)]
+    error InvalidAmount();
+    #[derive(Debug)]
+    error TransferFailed();
+    #[derive(Debug)]
+    error ContentTooLong();
+    #[derive(Debug)]
+    error IdentityAlreadyRegistered();
+    event SessionCreated(uint64 indexed id, address indexed creator, string name);
+    event VoterRegistered(address indexed voter, bytes32 identity);
+    event VoteCast(uint64 indexed session_id, address indexed voter, uint256 total_credits_spent);
+    event SessionClosed(uint64 indexed id, uint256 end_time);
+    event SessionCancelled(uint64 indexed id);
+    event SessionExtended(uint64 indexed id, uint256 end_time);
+    event SessionPaused(uint64 indexed id, bool paused);
+    event RoleGranted(bytes32 indexed role, address indexed account, address indexed sender);
+    event RoleRevoked(bytes32 indexed role, address indexed account, address indexed sender);
+    event AdminTransferStarted(address indexed current_admin, address indexed pending_admin);
+    event RegistrationRequested(address indexed voter);
+    event VoterRemoved(address indexed voter);
+    event VoterSuspended(address indexed voter, bool suspended);
+    event VoteCommitted(uint64 indexed session_id, address indexed voter, bytes32 commitment);
+    event VoteRevealed(uint64 indexed session_id, address indexed voter);
+    event DelegateChanged(uint64 indexed session_id, address indexed delegator, address from_delegate, address to_delegate);
+    event BallotRejected(address indexed voter, bytes4 reason);
+    event CreditsDeposited(uint64 indexed session_id, address indexed voter, uint256 amount);
+    event CreditsWithdrawn(uint64 indexed session_id, address indexed voter, uint256 amount);
+    event MatchingPoolFunded(uint64 indexed session_id, address indexed funder, uint256 amount);
+    event ContributionMade(uint64 indexed session_id, uint16 indexed proposal_id, address indexed contributor, uint256 amount);
+    event MatchingFinalized(uint64 indexed session_id, uint256 total_matched);
+    event FundsClaimed(uint64 indexed session_id, uint16 indexed proposal_id, address indexed recipient, uint256 amount);
+    event FundsRefunded(uint64 indexed session_id, address indexed account, uint256 amount);
+    event MatchingDustSwept(uint64 indexed session_id, address indexed to, uint256 amount);
+    event ProposalSubmitted(uint64 indexed session_id, uint16 indexed proposal_id, address indexed proposer, string title);
+    event ProposalRejected(uint64 indexed session_id, uint16 indexed proposal_id);
+    event ForfeitedBondsWithdrawn(address indexed recipient, uint256 amount);
+    event TieResolved(uint64 indexed session_id, uint16 indexed proposal_id);
+    event SessionFinalized(uint64 indexed session_id, uint8 outcome, uint16 winner_id);
+    event WinnersSelected(uint64 indexed session_id, uint16[] proposal_ids);
+    event BudgetAllocated(uint64 indexed session_id, uint16[] funded_ids, uint256 remaining);
+}
+
+// Token calls are encoded with sol! and sent through the host VM rather than sol_interface!.
+// In SDK 0.9 the generated methods go through call::call and RawCall, which bypass the host VM
+// and cannot be exercised in TestVM
+sol! {
+    interface IERC20 {
+        function transfer(address to, uint256 amount) external returns (bool);
+        function transferFrom(address from, address to, uint256 amount) external returns (bool);
+    }
 }
 
 #[derive(SolidityError, Debug)]
@@ -63,31 +265,104 @@ pub enum QuadraticVotingError {
     InvalidVoteCount(InvalidVoteCount),
     Unauthorized(Unauthorized),
     InvalidProposalCount(InvalidProposalCount),
+    InvalidProposal(InvalidProposal),
+    InvalidMode(InvalidMode),
+    InvalidProof(InvalidProof),
+    InvalidCommitment(InvalidCommitment),
+    InvalidDelegate(InvalidDelegate),
+    DelegationCycle(DelegationCycle),
+    InvalidSignature(InvalidSignature),
+    SignatureExpired(SignatureExpired),
+    InvalidNonce(InvalidNonce),
+    TokenTransferFailed(TokenTransferFailed),
+    InvalidAmount(InvalidAmount),
+    TransferFailed(TransferFailed),
+    ContentTooLong(ContentTooLong),
+    IdentityAlreadyRegistered(IdentityAlreadyRegistered),
 }
 
 sol_storage! {
     pub struct Proposal {
-        bytes32 title;
-        bytes32 description;
+        string title;
+        string description;
         uint64 vote_count;
+        address recipient;
+        uint256 contributions;
+        uint256 sqrt_sum;
+        mapping(address => uint256) contributed;
+        uint256 match_amount;
+        bool claimed;
+        address proposer;
+        uint256 bond;
+        bool rejected;
+        uint64 reached_at;
+        uint64 against_count;
+        uint256 requested_amount;
     }
     pub struct Voter {
-        bytes32 email;
+        bytes32 identity;
         bool is_registered;
+        bool is_pending;
+        bool is_suspended;
     }
 
     pub struct VotingSession {
-        bytes32 name;
-        bytes32 description;
+        string name;
+        string description;
         uint256 start_time;
         uint256 end_time;
-        uint8 credits_per_voter;
+        uint256 credits_per_voter;
         bool active;
-        mapping(uint8 => Proposal) proposals;
-        uint8 proposal_count;
+        bool paused;
+        bool cancelled;
+        mapping(uint16 => Proposal) proposals;
+        uint16 proposal_count;
         address creator;
-        mapping(address => mapping(uint8 => uint64)) votes_per_proposal;
-        mapping(address => uint8) voter_credits;
+        mapping(address => mapping(uint16 => uint64)) votes_per_proposal;
+        mapping(address => mapping(uint16 => bool)) opposes;
+        mapping(address => uint256) credits_spent;
+        bytes32 eligibility_root;
+        mapping(address => uint256) voter_allocation;
+        mapping(address => bool) has_allocation;
+        uint64 reveal_duration;
+        mapping(address => bytes32) commitments;
+        mapping(address => address) delegates;
+        address credit_token;
+        mapping(address => uint256) deposits;
+        bool quadratic_funding;
+        address funding_token;
+        uint256 matching_pool;
+        bool matching_finalized;
+        uint256 total_matched;
+        mapping(address => uint256) pool_funded;
+        mapping(address => uint256) contributed_total;
+        uint16 leader;
+        uint64 leader_votes;
+        bool leader_stale;
+        uint256 total_votes;
+        uint16 submission_cap;
+        uint16 submission_count;
+        uint256 submission_bond;
+        uint8 tie_break;
+        uint16 tie_winner;
+        uint64 vote_sequence;
+        uint64 quorum_voters;
+        uint256 quorum_credits;
+        uint64 vote_threshold;
+        uint64 voter_count;
+        uint256 total_credits_spent;
+        bool finalized;
+        uint8 outcome;
+        uint16 winner;
+        uint16 winners_count;
+        bool budget_mode;
+        uint256 budget;
+        uint256 budget_remaining;
+        mapping(uint16 => uint16) funded_ids;
+        uint16 funded_count;
+        mapping(address => uint8) delegation_height;
+        mapping(address => mapping(uint8 => uint32)) delegators_at_height;
+        bool dust_swept;
     }
 
     #[entrypoint]
@@ -96,86 +371,298 @@ sol_storage! {
         mapping(address => Voter) voters;
         uint64 session_counter;
         address admin;
+        address pending_admin;
+        mapping(bytes32 => mapping(address => bool)) roles;
+        uint8 registration_mode;
+        mapping(address => uint256) nonces;
+        mapping(bytes32 => address) identity_owners;
+        mapping(address => uint256) forfeited_bonds;
+    }
+}
+
+impl Proposal {
+    /// @dev Support minus opposition. Both tallies are u64, so the difference always fits
+    fn net_score(&self) -> i128 {
+        self.vote_count.get().to::<u64>() as i128 - self.against_count.get().to::<u64>() as i128
+    }
+}
+
+impl VotingSession {
+    /// @dev Credit budget of a voter: the deposited token amount in token-backed sessions,
+    ///      a custom or proven allocation when one is recorded, otherwise credits_per_voter
+    ///      (or nothing in Merkle-gated sessions)
+    fn allocation_of(&self, voter: Address) -> U256 {
+        if self.credit_token.get() != Address::ZERO {
+            self.deposits.get(voter)
+        } else if self.has_allocation.get(voter) {
+            self.voter_allocation.get(voter)
+        } else if self.eligibility_root.get() == B256::ZERO {
+            self.credits_per_voter.get()
+        } else {
+            U256::ZERO
+        }
     }
 }
 
+// Optional entrypoint groups are built only with their Cargo feature, to keep a deployment within
+// the Stylus size limit. Without the feature the group is an empty interface
+#[cfg(not(feature = "quadratic-funding"))]
+pub trait QuadraticFunding {}
+#[cfg(not(feature = "quadratic-funding"))]
+#[public]
+impl QuadraticFunding for QuadraticVoting {}
+#[cfg(not(feature = "signed-ballots"))]
+pub trait SignedBallots {}
+#[cfg(not(feature = "signed-ballots"))]
+#[public]
+impl SignedBallots for QuadraticVoting {}
+#[cfg(not(feature = "token-credits"))]
+pub trait TokenCredits {}
+#[cfg(not(feature = "token-credits"))]
+#[public]
+impl TokenCredits for QuadraticVoting {}
+
 #[public]
+#[implements(QuadraticFunding, SignedBallots, TokenCredits)]
 impl QuadraticVoting {
     /// @notice Initialize the contract with admin privileges
-    /// @dev Sets the contract deployer as admin and initializes session counter
+    /// @dev Sets the contract deployer as admin, grants it every role and initializes session counter
     #[constructor]
     pub fn constructor(&mut self) {
         let admin = self.vm().msg_sender();
         self.admin.set(admin);
         self.session_counter.set(U64::ZERO);
+        for role in [ADMIN_ROLE, SESSION_CREATOR_ROLE, VOTER_REGISTRAR_ROLE, PAUSER_ROLE] {
+            self.set_role(role, admin, true);
+        }
     }
 
-    /// @notice Register a voter with their email address
-    /// @dev Registers a new voter if they haven't registered before
-    /// @param email The voter's email address (stored as bytes32)
+    /// @notice Register a voter with a salted identity commitment
+    /// @dev Registers a new voter if they haven't registered before. Each identity can back only
+    ///      one address. Without an attestation, approval mode only files a pending request and
+    ///      allowlist mode rejects self-registration; a valid registrar attestation admits the
+    ///      voter immediately in every mode
+    /// @param identity identity_commitment(email, salt); the email itself never reaches the chain
+    /// @param attestation Empty, or a registrar's signature over registration_digest(caller, identity)
     /// @return Result indicating success or specific error
-    pub fn register_voter(&mut self, email: String) -> Result<(), QuadraticVotingError> {
+    pub fn register_voter(&mut self, identity: B256, attestation: Bytes) -> Result<(), QuadraticVotingError> {
         let caller = self.vm().msg_sender();
+        let mode = self.registration_mode.get().to::<u8>();
+
+        let voter = self.voters.get(caller);
+        if voter.is_registered.get() || voter.is_pending.get() {
+            return Err(QuadraticVotingError::Unauthorized(Unauthorized {}));
+        }
+        if identity == B256::ZERO {
+            return Err(QuadraticVotingError::InvalidCommitment(InvalidCommitment {}));
+        }
+        let owner = self.identity_owners.get(identity);
+        if owner != Address::ZERO && owner != caller {
+            return Err(QuadraticVotingError::IdentityAlreadyRegistered(IdentityAlreadyRegistered {}));
+        }
 
-        if self.voters.get(caller).is_registered.get() {
+        let attested = !attestation.is_empty();
+        if attested {
+            let digest = eip712::registration_digest(
+                self.vm().chain_id(),
+                self.vm().contract_address(),
+                caller,
+                identity,
+            );
+            let registrar = self.recover_signer(digest, &attestation)?;
+            if !self.has_role(VOTER_REGISTRAR_ROLE, registrar) {
+                return Err(QuadraticVotingError::InvalidSignature(InvalidSignature {}));
+            }
+        } else if mode == REGISTRATION_ALLOWLIST {
             return Err(QuadraticVotingError::Unauthorized(Unauthorized {}));
         }
 
-        self.voters.setter(caller).email.set(to_b256(&email));
+        self.identity_owners.setter(identity).set(caller);
+        self.voters.setter(caller).identity.set(identity);
+        if !attested && mode == REGISTRATION_APPROVAL {
+            self.voters.setter(caller).is_pending.set(true);
+            log(self.vm(), RegistrationRequested { voter: caller });
+            return Ok(());
+        }
+
         self.voters.setter(caller).is_registered.set(true);
 
         log(
             self.vm(),
             VoterRegistered {
                 voter: caller,
-                email: to_b256(&email),
+                identity,
             },
         );
 
         Ok(())
     }
 
+    /// @notice Choose how new voters are admitted
+    /// @dev Requires ADMIN_ROLE. Existing registrations are unaffected
+    /// @param mode REGISTRATION_OPEN, REGISTRATION_APPROVAL or REGISTRATION_ALLOWLIST
+    /// @return Result indicating success or specific error
+    pub fn set_registration_mode(&mut self, mode: u8) -> Result<(), QuadraticVotingError> {
+        self.only_role(ADMIN_ROLE)?;
+        if mode > REGISTRATION_ALLOWLIST {
+            return Err(QuadraticVotingError::InvalidMode(InvalidMode {}));
+        }
+        self.registration_mode.set(U8::from(mode));
+        Ok(())
+    }
+
+    /// @notice Get the current registration mode
+    /// @return REGISTRATION_OPEN, REGISTRATION_APPROVAL or REGISTRATION_ALLOWLIST
+    pub fn get_registration_mode(&self) -> u8 {
+        self.registration_mode.get().to::<u8>()
+    }
+
+    /// @notice Register several voters at once, regardless of the registration mode
+    /// @dev Requires VOTER_REGISTRAR_ROLE. Addresses that are already registered are skipped
+    /// @param voters The addresses to register
+    /// @return Result indicating success or specific error
+    pub fn register_voters(&mut self, voters: Vec<Address>) -> Result<(), QuadraticVotingError> {
+        self.only_role(VOTER_REGISTRAR_ROLE)?;
+        for voter in voters {
+            self.admit_voter(voter);
+        }
+        Ok(())
+    }
+
+    /// @notice Approve pending registration requests
+    /// @dev Requires VOTER_REGISTRAR_ROLE. Every address must have a pending request
+    /// @param voters The pending addresses to approve
+    /// @return Result indicating success or specific error
+    pub fn approve_voters(&mut self, voters: Vec<Address>) -> Result<(), QuadraticVotingError> {
+        self.only_role(VOTER_REGISTRAR_ROLE)?;
+        if voters.iter().any(|&voter| !self.voters.get(voter).is_pending.get()) {
+            return Err(QuadraticVotingError::VoterNotRegistered(VoterNotRegistered {}));
+        }
+        for voter in voters {
+            self.admit_voter(voter);
+        }
+        Ok(())
+    }
+
+    /// @notice Suspend or reinstate a registered voter
+    /// @dev Requires VOTER_REGISTRAR_ROLE. Suspended voters keep their registration but cannot vote
+    /// @param voter The registered voter
+    /// @param suspended Whether the voter should be suspended
+    /// @return Result indicating success or specific error
+    pub fn set_voter_suspended(
+        &mut self,
+        voter: Address,
+        suspended: bool,
+    ) -> Result<(), QuadraticVotingError> {
+        self.only_role(VOTER_REGISTRAR_ROLE)?;
+        if !self.voters.get(voter).is_registered.get() {
+            return Err(QuadraticVotingError::VoterNotRegistered(VoterNotRegistered {}));
+        }
+
+        self.voters.setter(voter).is_suspended.set(suspended);
+        log(self.vm(), VoterSuspended { voter, suspended });
+        Ok(())
+    }
+
+    /// @notice Deregister a voter or reject a pending request
+    /// @dev Requires VOTER_REGISTRAR_ROLE. Votes already cast remain counted
+    /// @param voter The voter to remove
+    /// @return Result indicating success or specific error
+    pub fn remove_voter(&mut self, voter: Address) -> Result<(), QuadraticVotingError> {
+        self.only_role(VOTER_REGISTRAR_ROLE)?;
+        let record = self.voters.get(voter);
+        if !record.is_registered.get() && !record.is_pending.get() {
+            return Err(QuadraticVotingError::VoterNotRegistered(VoterNotRegistered {}));
+        }
+
+        // The identity commitment stays reserved for this address, so it cannot be reused by
+        // another account. Attestations do not expire: suspend voters who must stay out
+        let mut record = self.voters.setter(voter);
+        record.identity.set(B256::ZERO);
+        record.is_registered.set(false);
+        record.is_pending.set(false);
+        record.is_suspended.set(false);
+
+        log(self.vm(), VoterRemoved { voter });
+        Ok(())
+    }
+
+    /// @notice Get a voter's registration state
+    /// @param voter The address to query
+    /// @return Tuple containing (registered, pending, suspended)
+    pub fn get_voter_status(&self, voter: Address) -> (bool, bool, bool) {
+        let record = self.voters.get(voter);
+        (
+            record.is_registered.get(),
+            record.is_pending.get(),
+            record.is_suspended.get(),
+        )
+    }
+
+    /// @notice Get the address an identity commitment is registered to
+    /// @param identity The identity commitment to look up
+    /// @return The owning address, or zero if the identity is unused
+    pub fn get_identity_owner(&self, identity: B256) -> Address {
+        self.identity_owners.get(identity)
+    }
+
     /// @notice Create a new voting session with proposals
-    /// @dev Only admin can create sessions. Initializes session storage and adds proposals
-    /// @param name Session name (stored as bytes32)
-    /// @param description Session description (stored as bytes32)
+    /// @dev Requires SESSION_CREATOR_ROLE. Initializes session storage and adds proposals
+    /// @param name Session name (at most MAX_TITLE_LENGTH bytes)
+    /// @param description Session description (at most MAX_DESCRIPTION_LENGTH bytes)
     /// @param credits_per_voter Base credits allocated to each voter for this session
-    /// @param duration_seconds How long the session will remain active
+    /// @param start_time Unix timestamp at which voting opens (0 opens voting immediately)
+    /// @param duration_seconds How long the session will remain active after it starts
     /// @param initial_proposals Array of (title, description) pairs for proposals
+    /// @param eligibility_root Merkle root of eligible (address, credit_allocation) leaves, or zero
+    ///        to admit every registered voter with credits_per_voter
+    /// @param winners_count How many top proposals the session selects, at least 1
     /// @return The unique session ID assigned to this session
+    #[allow(clippy::too_many_arguments)]
     pub fn create_session(
         &mut self,
         name: String,
         description: String,
-        credits_per_voter: U8,
+        credits_per_voter: U256,
+        start_time: U64,
         duration_seconds: U64,
         initial_proposals: Vec<(String, String)>,
+        eligibility_root: B256,
+        winners_count: U16,
     ) -> Result<U64, QuadraticVotingError> {
+        self.only_role(SESSION_CREATOR_ROLE)?;
         let caller = self.vm().msg_sender();
 
-        if caller != self.admin.get() {
-            return Err(QuadraticVotingError::Unauthorized(Unauthorized {}));
-        }
-
-        if initial_proposals.is_empty() {
+        if initial_proposals.is_empty() || winners_count == U16::ZERO {
             return Err(QuadraticVotingError::InvalidProposalCount(
                 InvalidProposalCount {},
             ));
         }
+        check_content(&name, &description)?;
+        check_proposals(&initial_proposals)?;
+
+        let now = U256::from(self.vm().block_timestamp());
+        let start_time = if start_time == U64::ZERO {
+            now
+        } else {
+            U256::from(start_time)
+        };
+        if start_time < now {
+            return Err(QuadraticVotingError::InvalidSession(InvalidSession {}));
+        }
 
-        let current_block = U256::from(self.vm().block_number());
         let session_id = self.session_counter.get() + U64::from(1);
-        let end_time = current_block + U256::from(duration_seconds.as_limbs()[0]);
+        let end_time = start_time + U256::from(duration_seconds);
 
-        self.sessions.setter(session_id).name.set(to_b256(&name));
+        self.sessions.setter(session_id).name.set_str(&name);
         self.sessions
             .setter(session_id)
             .description
-            .set(to_b256(&description));
+            .set_str(&description);
         self.sessions
             .setter(session_id)
             .start_time
-            .set(current_block);
+            .set(start_time);
         self.sessions
             .setter(session_id)
             .end_time
@@ -186,6 +673,14 @@ impl QuadraticVoting {
             .set(credits_per_voter);
         self.sessions.setter(session_id).active.set(true);
         self.sessions.setter(session_id).creator.set(caller);
+        self.sessions
+            .setter(session_id)
+            .eligibility_root
+            .set(eligibility_root);
+        self.sessions
+            .setter(session_id)
+            .winners_count
+            .set(winners_count);
 
         self.session_counter.set(session_id);
 
@@ -194,7 +689,7 @@ impl QuadraticVoting {
             SessionCreated {
                 id: session_id.as_limbs()[0],
                 creator: caller,
-                name: to_b256(&name),
+                name,
             },
         );
 
@@ -203,235 +698,1978 @@ impl QuadraticVoting {
         Ok(session_id)
     }
 
-    /// @notice Get comprehensive session details
-    /// @dev Returns all session metadata including timing, credits, and proposal count
-    /// @param session_id The session to query
-    /// @return Tuple containing (name, description, start_time, end_time, credits_per_voter, active, creator, proposal_count)
-    pub fn get_session(
-        &self,
-        session_id: U64,
-    ) -> Result<(String, String, U256, U256, U8, bool, Address, U8), QuadraticVotingError> {
-        let session = self.sessions.get(session_id);
-        if session.creator.get() == Address::ZERO {
+    /// @notice Close a session before its scheduled end
+    /// @dev Only the session creator or an ADMIN_ROLE holder can close. Results remain valid
+    /// @param session_id The session to close
+    /// @return Result indicating success or specific error
+    pub fn close_session(&mut self, session_id: U64) -> Result<(), QuadraticVotingError> {
+        self.only_session_manager(session_id, ADMIN_ROLE)?;
+        if !self.sessions.get(session_id).active.get() {
             return Err(QuadraticVotingError::InvalidSession(InvalidSession {}));
         }
 
-        Ok((
-            from_b256(session.name.get()),
-            from_b256(session.description.get()),
-            session.start_time.get(),
-            session.end_time.get(),
-            session.credits_per_voter.get(),
-            session.active.get(),
-            session.creator.get(),
-            session.proposal_count.get(),
-        ))
-    }
-
+        let now = U256::from(self.vm().block_timestamp());
+        let mut session = self.sessions.setter(session_id);
+        if now < session.end_time.get() {
+            session.end_time.set(now);
+        }
+        session.active.set(false);
+        let end_time = session.end_time.get();
 
-    /// @notice Cast votes for multiple proposals using quadratic voting
-    /// @dev Implements quadratic cost voting where vote_cost = vote_intensity²
-    /// @param session_id The session to vote in
-    /// @param proposal_ids Array of proposal IDs to vote for
-    /// @param vote_counts Corresponding vote intensities (cost = intensity²)
-    /// @return Result indicating success or specific error
-    pub fn vote(
-        &mut self,
-        session_id: U64,
-        proposal_ids: Vec<U8>,
-        vote_counts: Vec<U64>,
-    ) -> Result<(), QuadraticVotingError> {
-        let caller = self.vm().msg_sender();
+        log(
+            self.vm(),
+            SessionClosed {
+                id: session_id.as_limbs()[0],
+                end_time,
+            },
+        );
 
-        if proposal_ids.len() != vote_counts.len() {
-            return Err(QuadraticVotingError::InvalidVoteCount(InvalidVoteCount {}));
-        }
-        if !self.voters.get(caller).is_registered.get() {
-            return Err(QuadraticVotingError::VoterNotRegistered(VoterNotRegistered {}));
-        }
+        Ok(())
+    }
 
-        let session_data = self.sessions.get(session_id);
-        if session_data.creator.get() == Address::ZERO {
-            return Err(QuadraticVotingError::InvalidSession(InvalidSession {}));
-        }
-        if !session_data.active.get() {
-            return Err(QuadraticVotingError::InvalidSession(InvalidSession {}));
-        }
-        let current_block = U256::from(self.vm().block_number());
-        if current_block >= session_data.end_time.get() {
+    /// @notice Cancel a session and void its results
+    /// @dev Only the session creator or an ADMIN_ROLE holder can cancel. Closed sessions can still be
+    ///      cancelled, finalized ones cannot
+    /// @param session_id The session to cancel
+    /// @return Result indicating success or specific error
+    pub fn cancel_session(&mut self, session_id: U64) -> Result<(), QuadraticVotingError> {
+        self.only_session_manager(session_id, ADMIN_ROLE)?;
+        let session = self.sessions.get(session_id);
+        if session.cancelled.get() || session.matching_finalized.get() || session.finalized.get() {
             return Err(QuadraticVotingError::InvalidSession(InvalidSession {}));
         }
 
-        let mut total_credits_needed = U64::ZERO;
-        for &vote_count in vote_counts.iter() {
-            let cost = vote_count.saturating_mul(vote_count);
-            total_credits_needed = total_credits_needed.saturating_add(cost);
-        }
-
-        let mut voter_credits = session_data.voter_credits.get(caller);
-        if voter_credits == U8::ZERO {
-            voter_credits = session_data.credits_per_voter.get();
-        }
-        let credits_needed_u8 = U8::from(total_credits_needed.as_limbs()[0]);
-        if voter_credits < credits_needed_u8 {
-            return Err(QuadraticVotingError::InsufficientCredits(InsufficientCredits {}));
-        }
+        let mut session = self.sessions.setter(session_id);
+        session.active.set(false);
+        session.cancelled.set(true);
 
-        let mut vote_updates = Vec::new();
-        for (i, &proposal_id) in proposal_ids.iter().enumerate() {
-            let vote_count = vote_counts[i];
+        log(
+            self.vm(),
+            SessionCancelled {
+                id: session_id.as_limbs()[0],
+            },
+        );
 
-            let current_votes = session_data.votes_per_proposal.get(caller).get(proposal_id);
-            let current_proposal_votes = session_data.proposals.get(proposal_id).vote_count.get();
+        Ok(())
+    }
 
-            let new_votes = vote_count;
-            let new_proposal_votes = current_proposal_votes
-                .saturating_sub(current_votes)
-                .saturating_add(new_votes);
+    /// @notice Push back the end of a session
+    /// @dev Only the session creator or an ADMIN_ROLE holder can extend. A session whose end_time has
+    ///      already passed is reopened; sessions closed early, cancelled or with finalized matching
+    ///      cannot be extended, and token-backed sessions cannot be reopened because deposits unlock
+    ///      at end_time. Commit-reveal sessions cannot be extended once the commit phase is over,
+    ///      since reopening it would let voters commit again after seeing revealed ballots
+    /// @param session_id The session to extend
+    /// @param new_end_time Unix timestamp later than both the current end_time and now
+    /// @return Result indicating success or specific error
+    pub fn extend_session(
+        &mut self,
+        session_id: U64,
+        new_end_time: U64,
+    ) -> Result<(), QuadraticVotingError> {
+        self.only_session_manager(session_id, ADMIN_ROLE)?;
 
-            vote_updates.push((proposal_id, new_votes, new_proposal_votes));
+        let now = U256::from(self.vm().block_timestamp());
+        let new_end_time = U256::from(new_end_time);
+        let session = self.sessions.get(session_id);
+        if !session.active.get() || new_end_time <= session.end_time.get() || new_end_time <= now {
+            return Err(QuadraticVotingError::InvalidSession(InvalidSession {}));
         }
-
-        for (proposal_id, new_votes, new_proposal_votes) in vote_updates {
-            self.sessions
-                .setter(session_id)
-                .proposals
-                .setter(proposal_id)
-                .vote_count
-                .set(new_proposal_votes);
-
-            self.sessions
-                .setter(session_id)
-                .votes_per_proposal
-                .setter(caller)
-                .setter(proposal_id)
-                .set(new_votes);
+        let ended = now >= session.end_time.get();
+        if session.matching_finalized.get()
+            || (session.credit_token.get() != Address::ZERO && ended)
+            || (session.reveal_duration.get() != U64::ZERO && ended)
+        {
+            return Err(QuadraticVotingError::InvalidSession(InvalidSession {}));
         }
 
-        let new_remaining_credits = voter_credits.saturating_sub(credits_needed_u8);
-        self.sessions
-            .setter(session_id)
-            .voter_credits
-            .setter(caller)
-            .set(new_remaining_credits);
+        self.sessions.setter(session_id).end_time.set(new_end_time);
 
         log(
             self.vm(),
-            VoteCast {
-                session_id: session_id.as_limbs()[0],
-                voter: caller,
-                total_credits_spent: total_credits_needed.as_limbs()[0],
+            SessionExtended {
+                id: session_id.as_limbs()[0],
+                end_time: new_end_time,
             },
         );
 
         Ok(())
     }
 
+    /// @notice Temporarily stop accepting votes in a session
+    /// @dev Only the session creator or a PAUSER_ROLE holder can pause. The session window keeps running
+    /// @param session_id The session to pause
+    /// @return Result indicating success or specific error
+    pub fn pause_session(&mut self, session_id: U64) -> Result<(), QuadraticVotingError> {
+        self.set_session_paused(session_id, true)
+    }
+
+    /// @notice Resume voting in a paused session
+    /// @dev Only the session creator or a PAUSER_ROLE holder can resume
+    /// @param session_id The session to resume
+    /// @return Result indicating success or specific error
+    pub fn resume_session(&mut self, session_id: U64) -> Result<(), QuadraticVotingError> {
+        self.set_session_paused(session_id, false)
+    }
 
-    /// @notice Get voting results summary for a session
-    /// @dev Returns winner ID, proposal count, max votes, and total votes across all proposals
-    /// @param session_id The session to get results for
-    /// @return Tuple containing (winner_proposal_id, total_proposals, max_votes_received, total_votes_cast)
-    pub fn get_session_results(
+    /// @notice Get the lifecycle flags of a session
+    /// @param session_id The session to query
+    /// @return Tuple containing (active, paused, cancelled)
+    pub fn get_session_status(
         &self,
         session_id: U64,
-    ) -> Result<(U8, U8, U64, U64), QuadraticVotingError> {
+    ) -> Result<(bool, bool, bool), QuadraticVotingError> {
         let session = self.sessions.get(session_id);
         if session.creator.get() == Address::ZERO {
             return Err(QuadraticVotingError::InvalidSession(InvalidSession {}));
         }
 
-        let proposal_count = session.proposal_count.get();
-        let mut total_votes = U64::ZERO;
-        let mut winner_id = U8::ZERO;
-        let mut max_votes = U64::ZERO;
+        Ok((
+            session.active.get(),
+            session.paused.get(),
+            session.cancelled.get(),
+        ))
+    }
 
-        for i in 1..=proposal_count.as_limbs()[0] {
-            let proposal_id = U8::from(i);
-            let proposal = session.proposals.get(proposal_id);
-            let vote_count = proposal.vote_count.get();
+    /// @notice Assign custom credit budgets to voters, e.g. by membership tier
+    /// @dev Only the session creator or an ADMIN_ROLE holder, and only before start_time.
+    ///      Unlisted registered voters keep credits_per_voter. Not available in Merkle-gated sessions
+    /// @param session_id The session to configure
+    /// @param voters The voters receiving a custom budget
+    /// @param amounts The credit budget for each voter
+    /// @return Result indicating success or specific error
+    pub fn set_voter_credits(
+        &mut self,
+        session_id: U64,
+        voters: Vec<Address>,
+        amounts: Vec<U256>,
+    ) -> Result<(), QuadraticVotingError> {
+        self.only_session_manager(session_id, ADMIN_ROLE)?;
+        if voters.len() != amounts.len() {
+            return Err(QuadraticVotingError::InvalidVoteCount(InvalidVoteCount {}));
+        }
 
-            total_votes = total_votes.saturating_add(vote_count);
+        let now = U256::from(self.vm().block_timestamp());
+        let session = self.sessions.get(session_id);
+        if now >= session.start_time.get()
+            || session.eligibility_root.get() != B256::ZERO
+            || session.credit_token.get() != Address::ZERO
+        {
+            return Err(QuadraticVotingError::InvalidSession(InvalidSession {}));
+        }
 
-            if vote_count > max_votes {
-                max_votes = vote_count;
-                winner_id = proposal_id;
-            }
+        let mut session = self.sessions.setter(session_id);
+        for (voter, amount) in voters.into_iter().zip(amounts) {
+            session.voter_allocation.setter(voter).set(amount);
+            session.has_allocation.setter(voter).set(true);
         }
 
-        Ok((winner_id, proposal_count, max_votes, total_votes))
+        Ok(())
     }
 
-    /// @notice Get all proposals in a session with their current vote counts
-    /// @dev Returns detailed information for all proposals, limited to MAX_PROPOSALS_PER_SESSION
+    /// @notice Get a voter's credit budget for a session
     /// @param session_id The session to query
-    /// @return Array of tuples containing (proposal_id, title, description, current_vote_count)
-    pub fn get_session_proposals(
+    /// @param voter The voter address
+    /// @return Tuple containing (allocation, is_custom) where is_custom is false for the credits_per_voter default
+    pub fn get_voter_allocation(
         &self,
         session_id: U64,
-    ) -> Result<Vec<(U8, String, String, U64)>, QuadraticVotingError> {
+        voter: Address,
+    ) -> Result<(U256, bool), QuadraticVotingError> {
         let session = self.sessions.get(session_id);
         if session.creator.get() == Address::ZERO {
             return Err(QuadraticVotingError::InvalidSession(InvalidSession {}));
         }
 
-        let proposal_count = session.proposal_count.get();
-
-        let limit = if proposal_count > U8::from(MAX_PROPOSALS_PER_SESSION) {
-            U8::from(MAX_PROPOSALS_PER_SESSION)
-        } else {
-            proposal_count
-        };
-
-        let mut proposals = Vec::with_capacity(limit.as_limbs()[0] as usize);
+        Ok((session.allocation_of(voter), session.has_allocation.get(voter)))
+    }
 
-        for i in 1..=limit.as_limbs()[0] {
-            let id = U8::from(i);
-            let proposal = session.proposals.get(id);
+    /// @notice Switch a session to private commit-reveal voting
+    /// @dev Only the session creator or an ADMIN_ROLE holder, and only before start_time.
+    ///      Voters commit between start_time and end_time and reveal during the following
+    ///      reveal_duration seconds; commitments that are never revealed are not counted.
+    ///      Merkle-gated sessions cannot switch, since their voters prove eligibility with
+    ///      vote_with_proof, which votes in the clear
+    /// @param session_id The session to configure
+    /// @param reveal_duration Length of the reveal phase in seconds
+    /// @return Result indicating success or specific error
+    pub fn enable_commit_reveal(
+        &mut self,
+        session_id: U64,
+        reveal_duration: U64,
+    ) -> Result<(), QuadraticVotingError> {
+        self.only_session_manager(session_id, ADMIN_ROLE)?;
 
-            proposals.push((
-                id,
-                from_b256(proposal.title.get()),
-                from_b256(proposal.description.get()),
-                proposal.vote_count.get(),
-            ));
+        let now = U256::from(self.vm().block_timestamp());
+        let session = self.sessions.get(session_id);
+        if reveal_duration == U64::ZERO
+            || now >= session.start_time.get()
+            || session.eligibility_root.get() != B256::ZERO
+        {
+            return Err(QuadraticVotingError::InvalidSession(InvalidSession {}));
         }
 
-        Ok(proposals)
+        self.sessions
+            .setter(session_id)
+            .reveal_duration
+            .set(reveal_duration);
+        Ok(())
     }
 
+    /// @notice Get the reveal phase of a commit-reveal session
+    /// @param session_id The session to query
+    /// @return Tuple containing (reveal_start, reveal_end); both zero when the session votes in the clear
+    pub fn get_reveal_window(&self, session_id: U64) -> Result<(U256, U256), QuadraticVotingError> {
+        let session = self.sessions.get(session_id);
+        if session.creator.get() == Address::ZERO {
+            return Err(QuadraticVotingError::InvalidSession(InvalidSession {}));
+        }
 
+        let reveal_duration = session.reveal_duration.get();
+        if reveal_duration == U64::ZERO {
+            return Ok((U256::ZERO, U256::ZERO));
+        }
+        let reveal_start = session.end_time.get();
+        Ok((reveal_start, reveal_start + U256::from(reveal_duration)))
+    }
 
-}
-
-impl QuadraticVoting {
-    /// @dev Internal helper to add proposals to a newly created session
-    /// @param session_id The session to add proposals to
-    /// @param proposals Array of (title, description) tuples
-    fn add_proposals_to_session(
+    /// @notice Let voters submit proposals until the session starts
+    /// @dev Only the session creator or an ADMIN_ROLE holder, and only before start_time. The time
+    ///      before start_time becomes the proposal phase, during which registered voters submit
+    ///      for free and, if `bond` is non-zero, anyone else by posting `bond` wei
+    /// @param session_id The session to configure
+    /// @param cap Maximum number of submissions accepted, rejected ones included
+    /// @param bond ETH bond for submitters who are not registered voters; zero to require registration
+    /// @return Result indicating success or specific error
+    pub fn enable_proposal_submissions(
         &mut self,
         session_id: U64,
-        proposals: Vec<(String, String)>,
+        cap: U16,
+        bond: U256,
     ) -> Result<(), QuadraticVotingError> {
-        if proposals.len() > MAX_PROPOSALS_PER_SESSION as usize {
+        self.only_session_manager(session_id, ADMIN_ROLE)?;
+
+        let now = U256::from(self.vm().block_timestamp());
+        if now >= self.sessions.get(session_id).start_time.get() {
+            return Err(QuadraticVotingError::InvalidSession(InvalidSession {}));
+        }
+        if cap == U16::ZERO {
             return Err(QuadraticVotingError::InvalidProposalCount(InvalidProposalCount {}));
         }
 
+        let mut session = self.sessions.setter(session_id);
+        session.submission_cap.set(cap);
+        session.submission_bond.set(bond);
+        Ok(())
+    }
+
+    /// @notice Submit a proposal during a session's proposal phase
+    /// @dev Registered voters submit for free; anyone else must send exactly the session's bond,
+    ///      which is returned through reclaim_bond unless the proposal is rejected. In quadratic
+    ///      funding rounds the submitter becomes the proposal's recipient
+    /// @param session_id The session to submit to
+    /// @param title Proposal title (at most MAX_TITLE_LENGTH bytes)
+    /// @param description Proposal description (at most MAX_DESCRIPTION_LENGTH bytes)
+    /// @return The new proposal ID
+    #[payable]
+    pub fn submit_proposal(
+        &mut self,
+        session_id: U64,
+        title: String,
+        description: String,
+    ) -> Result<U16, QuadraticVotingError> {
+        let caller = self.vm().msg_sender();
+        let now = U256::from(self.vm().block_timestamp());
+        let session = self.sessions.get(session_id);
+        let cap = session.submission_cap.get();
+        if cap == U16::ZERO
+            || session.cancelled.get()
+            || session.budget_mode.get()
+            || now >= session.start_time.get()
+        {
+            return Err(QuadraticVotingError::InvalidSession(InvalidSession {}));
+        }
+        let submission_count = session.submission_count.get();
+        let proposal_count = session.proposal_count.get();
+        if submission_count >= cap || proposal_count >= U16::from(MAX_PROPOSALS_PER_SESSION) {
+            return Err(QuadraticVotingError::InvalidProposalCount(InvalidProposalCount {}));
+        }
+
+        let voter = self.voters.get(caller);
+        let bond = if voter.is_registered.get() {
+            if voter.is_suspended.get() {
+                return Err(QuadraticVotingError::Unauthorized(Unauthorized {}));
+            }
+            U256::ZERO
+        } else {
+            let bond = session.submission_bond.get();
+            if bond.is_zero() {
+                return Err(QuadraticVotingError::VoterNotRegistered(VoterNotRegistered {}));
+            }
+            bond
+        };
+        if self.vm().msg_value() != bond {
+            return Err(QuadraticVotingError::InvalidAmount(InvalidAmount {}));
+        }
+        let quadratic_funding = session.quadratic_funding.get();
+
+        self.add_proposals_to_session(session_id, vec![(title.clone(), description)])?;
+        let proposal_id = proposal_count + U16::from(1);
+        let mut session = self.sessions.setter(session_id);
+        session.submission_count.set(submission_count + U16::from(1));
+        let mut proposal = session.proposals.setter(proposal_id);
+        proposal.proposer.set(caller);
+        proposal.bond.set(bond);
+        if quadratic_funding {
+            proposal.recipient.set(caller);
+        }
+
+        log(
+            self.vm(),
+            ProposalSubmitted {
+                session_id: session_id.as_limbs()[0],
+                proposal_id: proposal_id.to::<u16>(),
+                proposer: caller,
+                title,
+            },
+        );
+
+        Ok(proposal_id)
+    }
+
+    /// @notice Reject a submitted proposal as spam before voting starts
+    /// @dev Only the session creator or an ADMIN_ROLE holder, and only before start_time. The
+    ///      proposal can no longer receive votes or contributions and is hidden from
+    ///      get_session_proposals. Its bond, if any, is forfeited to the session creator, who
+    ///      collects it with withdraw_forfeited_bonds
+    /// @param session_id The session the proposal belongs to
+    /// @param proposal_id The submitted proposal to reject
+    /// @return Result indicating success or specific error
+    pub fn reject_proposal(&mut self, session_id: U64, proposal_id: U16) -> Result<(), QuadraticVotingError> {
+        self.only_session_manager(session_id, ADMIN_ROLE)?;
+
+        let now = U256::from(self.vm().block_timestamp());
+        let session = self.sessions.get(session_id);
+        if now >= session.start_time.get() {
+            return Err(QuadraticVotingError::InvalidSession(InvalidSession {}));
+        }
+        if proposal_id == U16::ZERO || proposal_id > session.proposal_count.get() {
+            return Err(QuadraticVotingError::InvalidProposal(InvalidProposal {}));
+        }
+        let proposal = session.proposals.get(proposal_id);
+        if proposal.proposer.get() == Address::ZERO || proposal.rejected.get() {
+            return Err(QuadraticVotingError::InvalidProposal(InvalidProposal {}));
+        }
+        let bond = proposal.bond.get();
+        let creator = session.creator.get();
+
+        let mut session = self.sessions.setter(session_id);
+        let mut proposal = session.proposals.setter(proposal_id);
+        proposal.rejected.set(true);
+        proposal.bond.set(U256::ZERO);
+        let forfeited = self.forfeited_bonds.get(creator);
+        self.forfeited_bonds.setter(creator).set(forfeited + bond);
+
+        log(
+            self.vm(),
+            ProposalRejected {
+                session_id: session_id.as_limbs()[0],
+                proposal_id: proposal_id.to::<u16>(),
+            },
+        );
+
+        Ok(())
+    }
+
+    /// @notice Get a submitted proposal's bond back once the proposal phase is over
+    /// @dev Only the proposer, after start_time (or once the session is cancelled), for proposals
+    ///      that were not rejected
+    /// @param session_id The session the proposal belongs to
+    /// @param proposal_id The submitted proposal
+    /// @return The bond returned
+    pub fn reclaim_bond(&mut self, session_id: U64, proposal_id: U16) -> Result<U256, QuadraticVotingError> {
+        let caller = self.vm().msg_sender();
+        let now = U256::from(self.vm().block_timestamp());
+        let session = self.sessions.get(session_id);
+        if !session.cancelled.get() && now < session.start_time.get() {
+            return Err(QuadraticVotingError::InvalidSession(InvalidSession {}));
+        }
+        let proposal = session.proposals.get(proposal_id);
+        if proposal.proposer.get() != caller {
+            return Err(QuadraticVotingError::Unauthorized(Unauthorized {}));
+        }
+        let bond = proposal.bond.get();
+        if bond.is_zero() {
+            return Err(QuadraticVotingError::InvalidAmount(InvalidAmount {}));
+        }
+
+        self.sessions
+            .setter(session_id)
+            .proposals
+            .setter(proposal_id)
+            .bond
+            .set(U256::ZERO);
+        self.vm()
+            .transfer_eth(caller, bond)
+            .map_err(|_| QuadraticVotingError::TransferFailed(TransferFailed {}))?;
+
+        Ok(bond)
+    }
+
+    /// @notice Collect the bonds forfeited by proposals rejected in the caller's sessions
+    /// @return The amount sent to the caller
+    pub fn withdraw_forfeited_bonds(&mut self) -> Result<U256, QuadraticVotingError> {
+        let caller = self.vm().msg_sender();
+        let amount = self.forfeited_bonds.get(caller);
+        if amount.is_zero() {
+            return Err(QuadraticVotingError::InvalidAmount(InvalidAmount {}));
+        }
+
+        self.forfeited_bonds.setter(caller).set(U256::ZERO);
+        self.vm()
+            .transfer_eth(caller, amount)
+            .map_err(|_| QuadraticVotingError::TransferFailed(TransferFailed {}))?;
+
+        log(
+            self.vm(),
+            ForfeitedBondsWithdrawn {
+                recipient: caller,
+                amount,
+            },
+        );
+        Ok(amount)
+    }
+
+    /// @notice Get the forfeited bonds an account can collect with withdraw_forfeited_bonds
+    /// @param account The session creator to query
+    /// @return The amount waiting to be withdrawn
+    pub fn get_forfeited_bonds(&self, account: Address) -> U256 {
+        self.forfeited_bonds.get(account)
+    }
+
+    /// @notice Get who submitted a proposal and its moderation state
+    /// @param session_id The session the proposal belongs to
+    /// @param proposal_id The proposal to query
+    /// @return Tuple containing (proposer, bond, rejected); proposer is zero for proposals given at creation
+    pub fn get_proposal_submission(
+        &self,
+        session_id: U64,
+        proposal_id: U16,
+    ) -> Result<(Address, U256, bool), QuadraticVotingError> {
+        let session = self.sessions.get(session_id);
+        if proposal_id == U16::ZERO || proposal_id > session.proposal_count.get() {
+            return Err(QuadraticVotingError::InvalidProposal(InvalidProposal {}));
+        }
+
+        let proposal = session.proposals.get(proposal_id);
+        Ok((proposal.proposer.get(), proposal.bond.get(), proposal.rejected.get()))
+    }
+
+    /// @notice Fund proposals from a fixed budget by vote ranking
+    /// @dev Only the session creator or an ADMIN_ROLE holder, and only before start_time. Calling it
+    ///      again replaces the budget and amounts. Closes proposal submissions, so run it after
+    ///      the proposal phase. finalize_session then funds proposals greedily, see get_budget_allocation
+    /// @param session_id The session to configure
+    /// @param budget Total amount available to proposals
+    /// @param requested_amounts Non-zero amount each proposal requests, in proposal ID order
+    /// @return Result indicating success or specific error
+    pub fn enable_budget_mode(
+        &mut self,
+        session_id: U64,
+        budget: U256,
+        requested_amounts: Vec<U256>,
+    ) -> Result<(), QuadraticVotingError> {
+        self.only_session_manager(session_id, ADMIN_ROLE)?;
+
+        let now = U256::from(self.vm().block_timestamp());
+        let session = self.sessions.get(session_id);
+        if session.cancelled.get() || now >= session.start_time.get() {
+            return Err(QuadraticVotingError::InvalidSession(InvalidSession {}));
+        }
+        if requested_amounts.len() != session.proposal_count.get().to::<usize>() {
+            return Err(QuadraticVotingError::InvalidProposalCount(InvalidProposalCount {}));
+        }
+        if budget.is_zero() || requested_amounts.contains(&U256::ZERO) {
+            return Err(QuadraticVotingError::InvalidAmount(InvalidAmount {}));
+        }
+
+        let mut session = self.sessions.setter(session_id);
+        session.budget_mode.set(true);
+        session.budget.set(budget);
+        for (index, amount) in requested_amounts.into_iter().enumerate() {
+            session
+                .proposals
+                .setter(U16::from(index + 1))
+                .requested_amount
+                .set(amount);
+        }
+        Ok(())
+    }
+
+    /// @notice Get the proposals a budget session funds and the budget left over
+    /// @dev Frozen by finalize_session; until then this previews the allocation for the current
+    ///      standings. Under TIE_BREAK_NONE, or TIE_BREAK_CREATOR before resolve_tie, funding stops
+    ///      at tied proposals that do not all fit the remaining budget
+    /// @param session_id The budget session to query
+    /// @return Tuple containing (finalized, funded_ids, remaining_budget)
+    pub fn get_budget_allocation(&self, session_id: U64) -> Result<(bool, Vec<U16>, U256), QuadraticVotingError> {
+        let session = self.sessions.get(session_id);
+        if !session.budget_mode.get() || session.cancelled.get() {
+            return Err(QuadraticVotingError::InvalidSession(InvalidSession {}));
+        }
+        if !session.finalized.get() {
+            let (funded, remaining, _) = self.allocate_budget(session_id);
+            return Ok((false, funded, remaining));
+        }
+
+        let funded = (1..=session.funded_count.get().to::<u16>())
+            .map(|i| session.funded_ids.get(U16::from(i)))
+            .collect();
+        Ok((true, funded, session.budget_remaining.get()))
+    }
+
+    /// @notice Get the amount a proposal requests from a budget session
+    /// @param session_id The session to query
+    /// @param proposal_id The proposal to query
+    /// @return The requested amount, 0 outside budget mode
+    pub fn get_requested_amount(&self, session_id: U64, proposal_id: U16) -> U256 {
+        self.sessions
+            .get(session_id)
+            .proposals
+            .get(proposal_id)
+            .requested_amount
+            .get()
+    }
+
+    /// @notice Get comprehensive session details
+    /// @dev Returns all session metadata including timing, credits, and proposal count
+    /// @param session_id The session to query
+    /// @return Tuple containing (name, description, start_time, end_time, credits_per_voter, active, creator, proposal_count, winners_count)
+    pub fn get_session(
+        &self,
+        session_id: U64,
+    ) -> Result<SessionInfo, QuadraticVotingError> {
+        let session = self.sessions.get(session_id);
+        if session.creator.get() == Address::ZERO {
+            return Err(QuadraticVotingError::InvalidSession(InvalidSession {}));
+        }
+
+        Ok((
+            session.name.get_string(),
+            session.description.get_string(),
+            session.start_time.get(),
+            session.end_time.get(),
+            session.credits_per_voter.get(),
+            session.active.get(),
+            session.creator.get(),
+            session.proposal_count.get(),
+            session.winners_count.get(),
+        ))
+    }
+
+    /// @notice Get a voter's credit ledger for a session
+    /// @dev Credits are derived from the amount spent, so spending every credit never resets the budget.
+    ///      In Merkle-gated sessions the allocation is zero until the voter proves eligibility
+    /// @param session_id The session to query
+    /// @param voter The voter address
+    /// @return Tuple containing (allocated, spent, remaining)
+    pub fn get_voter_session_credits(
+        &self,
+        session_id: U64,
+        voter: Address,
+    ) -> Result<(U256, U256, U256), QuadraticVotingError> {
+        let session = self.sessions.get(session_id);
+        if session.creator.get() == Address::ZERO {
+            return Err(QuadraticVotingError::InvalidSession(InvalidSession {}));
+        }
+
+        let allocated = session.allocation_of(voter);
+        let spent = session.credits_spent.get(voter);
+        Ok((allocated, spent, allocated.saturating_sub(spent)))
+    }
+
+
+    /// @notice Cast votes for multiple proposals using quadratic voting
+    /// @dev Implements quadratic cost voting where vote_cost = vote_intensity².
+    ///      Voting again on a proposal replaces the previous intensity; only the cost
+    ///      difference is charged, and lowering or withdrawing votes refunds credits
+    /// @param session_id The session to vote in
+    /// @param proposal_ids Array of proposal IDs to vote for (each existing and listed at most once)
+    /// @param vote_counts Corresponding vote intensities (cost = intensity²)
+    /// @return Result indicating success or specific error
+    pub fn vote(
+        &mut self,
+        session_id: U64,
+        proposal_ids: Vec<U16>,
+        vote_counts: Vec<U64>,
+    ) -> Result<(), QuadraticVotingError> {
+        let caller = self.vm().msg_sender();
+        self.check_not_delegated(session_id, caller)?;
+        self.cast_votes(session_id, caller, proposal_ids, vote_counts, Vec::new())
+    }
+
+    /// @notice Cast votes for or against multiple proposals
+    /// @dev Like vote, but a negative intensity opposes the proposal at the same quadratic cost.
+    ///      Each vote replaces the voter's previous intensity and direction on that proposal.
+    ///      Support and opposition are tallied separately, and winners are ranked by net score
+    /// @param session_id The session to vote in
+    /// @param proposal_ids Array of proposal IDs to vote on (each existing and listed at most once)
+    /// @param intensities Signed vote intensities; positive supports, negative opposes (cost = intensity²)
+    /// @return Result indicating success or specific error
+    pub fn vote_with_intensities(
+        &mut self,
+        session_id: U64,
+        proposal_ids: Vec<U16>,
+        intensities: Vec<I64>,
+    ) -> Result<(), QuadraticVotingError> {
+        let caller = self.vm().msg_sender();
+        self.check_not_delegated(session_id, caller)?;
+        let vote_counts = intensities.iter().map(|intensity| intensity.unsigned_abs()).collect();
+        let against = intensities.iter().map(|intensity| intensity.is_negative()).collect();
+        self.cast_votes(session_id, caller, proposal_ids, vote_counts, against)
+    }
+
+    /// @notice Cast votes in a Merkle-gated session, proving eligibility on first use
+    /// @dev The first call verifies (caller, allocation) against the session's eligibility root and
+    ///      records the allocation as the caller's credit budget. Later calls may pass an empty proof
+    /// @param session_id The session to vote in
+    /// @param proposal_ids Array of proposal IDs to vote for (each existing and listed at most once)
+    /// @param vote_counts Corresponding vote intensities (cost = intensity²)
+    /// @param against Whether each vote opposes its proposal, as in vote_with_intensities; empty to support all
+    /// @param allocation The credit allocation in the caller's eligibility leaf
+    /// @param proof Sibling hashes from the leaf up to the root
+    /// @return Result indicating success or specific error
+    pub fn vote_with_proof(
+        &mut self,
+        session_id: U64,
+        proposal_ids: Vec<U16>,
+        vote_counts: Vec<U64>,
+        against: Vec<bool>,
+        allocation: U256,
+        proof: Vec<B256>,
+    ) -> Result<(), QuadraticVotingError> {
+        let caller = self.vm().msg_sender();
+        self.check_not_delegated(session_id, caller)?;
+
+        let session = self.sessions.get(session_id);
+        let root = session.eligibility_root.get();
+        if root == B256::ZERO {
+            return Err(QuadraticVotingError::InvalidSession(InvalidSession {}));
+        }
+
+        if !session.has_allocation.get(caller) {
+            if !merkle::verify(&proof, root, merkle::leaf_hash(caller, allocation)) {
+                return Err(QuadraticVotingError::InvalidProof(InvalidProof {}));
+            }
+            let mut session = self.sessions.setter(session_id);
+            session.voter_allocation.setter(caller).set(allocation);
+            session.has_allocation.setter(caller).set(true);
+        }
+
+        self.cast_votes(session_id, caller, proposal_ids, vote_counts, against)
+    }
+
+
+    /// @notice Let another address vote with the caller's credits in a session
+    /// @dev The delegate votes through vote_as_delegate and spends the caller's own budget, so the
+    ///      quadratic cost is still paid per delegator and budgets are never pooled. Delegates may
+    ///      delegate further; the last address in the chain votes. Links that would close a loop, or
+    ///      make any chain through them longer than MAX_DELEGATION_DEPTH (counting the delegations
+    ///      leading to the caller as well as those after `to`), are rejected
+    /// @param session_id The session to delegate in
+    /// @param to The delegate
+    /// @return Result indicating success or specific error
+    pub fn delegate(&mut self, session_id: U64, to: Address) -> Result<(), QuadraticVotingError> {
+        let caller = self.vm().msg_sender();
+        self.check_voter(session_id, caller)?;
+
+        let session = self.sessions.get(session_id);
+        let now = U256::from(self.vm().block_timestamp());
+        if session.cancelled.get() || now >= session.end_time.get() {
+            return Err(QuadraticVotingError::InvalidSession(InvalidSession {}));
+        }
+        if to == Address::ZERO {
+            return Err(QuadraticVotingError::InvalidDelegate(InvalidDelegate {}));
+        }
+
+        // The longest chain through the new link is the longest one ending at the caller, the
+        // link itself and the chain after `to`, which is already within MAX_DELEGATION_DEPTH
+        let mut current = to;
+        let mut hops = session.delegation_height.get(caller).to::<usize>() + 1;
+        loop {
+            if current == caller {
+                return Err(QuadraticVotingError::DelegationCycle(DelegationCycle {}));
+            }
+            let next = session.delegates.get(current);
+            if next == Address::ZERO {
+                break;
+            }
+            hops += 1;
+            current = next;
+        }
+        if hops > MAX_DELEGATION_DEPTH {
+            return Err(QuadraticVotingError::InvalidDelegate(InvalidDelegate {}));
+        }
+
+        let from_delegate = session.delegates.get(caller);
+        if from_delegate != Address::ZERO {
+            self.update_delegation_heights(session_id, caller, from_delegate, false);
+        }
+        self.sessions
+            .setter(session_id)
+            .delegates
+            .setter(caller)
+            .set(to);
+        self.update_delegation_heights(session_id, caller, to, true);
+
+        log(
+            self.vm(),
+            DelegateChanged {
+                session_id: session_id.as_limbs()[0],
+                delegator: caller,
+                from_delegate,
+                to_delegate: to,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// @notice Take back voting rights given with delegate
+    /// @dev Votes already cast by the delegate remain in place
+    /// @param session_id The session to undelegate in
+    /// @return Result indicating success or specific error
+    pub fn undelegate(&mut self, session_id: U64) -> Result<(), QuadraticVotingError> {
+        let caller = self.vm().msg_sender();
+        let from_delegate = self.sessions.get(session_id).delegates.get(caller);
+        if from_delegate == Address::ZERO {
+            return Err(QuadraticVotingError::InvalidDelegate(InvalidDelegate {}));
+        }
+
+        self.update_delegation_heights(session_id, caller, from_delegate, false);
+        self.sessions
+            .setter(session_id)
+            .delegates
+            .setter(caller)
+            .set(Address::ZERO);
+
+        log(
+            self.vm(),
+            DelegateChanged {
+                session_id: session_id.as_limbs()[0],
+                delegator: caller,
+                from_delegate,
+                to_delegate: Address::ZERO,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// @notice Get a voter's delegation in a session
+    /// @param session_id The session to query
+    /// @param delegator The voter who may have delegated
+    /// @return Tuple containing (direct_delegate, effective_delegate) where the effective delegate
+    ///         is the end of the delegation chain and the only address allowed to vote
+    pub fn get_delegate(&self, session_id: U64, delegator: Address) -> (Address, Address) {
+        (
+            self.sessions.get(session_id).delegates.get(delegator),
+            self.resolve_delegate(session_id, delegator),
+        )
+    }
+
+    /// @notice Vote on behalf of a voter who delegated to the caller
+    /// @dev Same rules as vote, applied to the delegator's own votes and credit budget. A delegate
+    ///      suspended by an admin cannot vote for anyone either
+    /// @param session_id The session to vote in
+    /// @param delegator The voter whose credits are spent
+    /// @param proposal_ids Array of proposal IDs to vote for (each existing and listed at most once)
+    /// @param vote_counts Corresponding vote intensities (cost = intensity²)
+    /// @param against Whether each vote opposes its proposal, as in vote_with_intensities; empty to support all
+    /// @return Result indicating success or specific error
+    pub fn vote_as_delegate(
+        &mut self,
+        session_id: U64,
+        delegator: Address,
+        proposal_ids: Vec<U16>,
+        vote_counts: Vec<U64>,
+        against: Vec<bool>,
+    ) -> Result<(), QuadraticVotingError> {
+        let caller = self.vm().msg_sender();
+        if self.resolve_delegate(session_id, delegator) != caller
+            || self.voters.get(caller).is_suspended.get()
+        {
+            return Err(QuadraticVotingError::Unauthorized(Unauthorized {}));
+        }
+
+        self.cast_votes(session_id, delegator, proposal_ids, vote_counts, against)
+    }
+
+    /// @notice Commit to a hidden ballot in a commit-reveal session
+    /// @dev Committing again before end_time replaces the previous commitment. Voters who
+    ///      delegated must undelegate first
+    /// @param session_id The session to vote in
+    /// @param commitment vote_commitment(session_id, voter, proposal_ids, vote_counts, against, salt)
+    /// @return Result indicating success or specific error
+    pub fn commit_vote(
+        &mut self,
+        session_id: U64,
+        commitment: B256,
+    ) -> Result<(), QuadraticVotingError> {
+        let caller = self.vm().msg_sender();
+        self.check_voter(session_id, caller)?;
+        self.check_not_delegated(session_id, caller)?;
+        self.check_voting_open(session_id)?;
+        if self.sessions.get(session_id).reveal_duration.get() == U64::ZERO {
+            return Err(QuadraticVotingError::InvalidSession(InvalidSession {}));
+        }
+        if commitment == B256::ZERO {
+            return Err(QuadraticVotingError::InvalidCommitment(InvalidCommitment {}));
+        }
+
+        self.sessions
+            .setter(session_id)
+            .commitments
+            .setter(caller)
+            .set(commitment);
+
+        log(
+            self.vm(),
+            VoteCommitted {
+                session_id: session_id.as_limbs()[0],
+                voter: caller,
+                commitment,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// @notice Reveal a committed ballot and apply it to the tally
+    /// @dev Only during the reveal phase. The quadratic credit check is applied here, and each
+    ///      commitment can be revealed once. Voters who delegated after committing must undelegate
+    ///      before revealing, so their credits are not spent twice
+    /// @param session_id The session the commitment belongs to
+    /// @param proposal_ids Array of proposal IDs that were committed to
+    /// @param vote_counts Corresponding vote intensities that were committed to
+    /// @param against Vote directions that were committed to, as in vote_with_intensities; empty to support all
+    /// @param salt The secret salt used in the commitment
+    /// @return Result indicating success or specific error
+    pub fn reveal_vote(
+        &mut self,
+        session_id: U64,
+        proposal_ids: Vec<U16>,
+        vote_counts: Vec<U64>,
+        against: Vec<bool>,
+        salt: B256,
+    ) -> Result<(), QuadraticVotingError> {
+        let caller = self.vm().msg_sender();
+        self.check_voter(session_id, caller)?;
+        self.check_not_delegated(session_id, caller)?;
+
+        let (reveal_start, reveal_end) = self.get_reveal_window(session_id)?;
+        let session = self.sessions.get(session_id);
+        let now = U256::from(self.vm().block_timestamp());
+        if reveal_end == U256::ZERO
+            || session.cancelled.get()
+            || session.paused.get()
+            || now < reveal_start
+            || now >= reveal_end
+        {
+            return Err(QuadraticVotingError::InvalidSession(InvalidSession {}));
+        }
+
+        let commitment = session.commitments.get(caller);
+        if commitment == B256::ZERO
+            || commitment
+                != vote_commitment(session_id, caller, &proposal_ids, &vote_counts, &against, salt)
+        {
+            return Err(QuadraticVotingError::InvalidCommitment(InvalidCommitment {}));
+        }
+
+        self.tally_votes(session_id, caller, proposal_ids, vote_counts, against)?;
+        self.sessions
+            .setter(session_id)
+            .commitments
+            .setter(caller)
+            .set(B256::ZERO);
+
+        log(
+            self.vm(),
+            VoteRevealed {
+                session_id: session_id.as_limbs()[0],
+                voter: caller,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// @notice Get voting results summary for a session
+    /// @dev Returns winner ID, proposal count, the top net score (0 if none is positive), total
+    ///      votes cast for and against all proposals, and the outcome under the session's quorum
+    ///      and threshold rules. The winner has the highest net score; the leader and totals are
+    ///      maintained as votes are tallied, and proposals are rescanned only after a vote has
+    ///      lowered the leader's net score. Ties are settled by the session's tie-break policy;
+    ///      proposals are only scanned in multi-winner sessions or under policies other than
+    ///      TIE_BREAK_LOWEST_ID. The winner is the top selected proposal (see get_winners) and is
+    ///      0 unless the outcome is OUTCOME_PASSED. Until finalize_session the outcome reflects
+    ///      the current standings. Cancelled sessions are void and report InvalidSession
+    /// @param session_id The session to get results for
+    /// @return Tuple containing (winner_proposal_id, total_proposals, max_votes_received, total_votes_cast, outcome)
+    pub fn get_session_results(
+        &self,
+        session_id: U64,
+    ) -> Result<(U16, U16, U64, U64, u8), QuadraticVotingError> {
+        let session = self.sessions.get(session_id);
+        if session.creator.get() == Address::ZERO || session.cancelled.get() {
+            return Err(QuadraticVotingError::InvalidSession(InvalidSession {}));
+        }
+
+        // The total is kept wide and reported saturated, as summing per-proposal tallies did
+        let total_votes = U64::saturating_from(session.total_votes.get());
+        let (outcome, winner) = if session.finalized.get() {
+            (session.outcome.get().to::<u8>(), session.winner.get())
+        } else {
+            self.current_outcome(session_id)
+        };
+        Ok((
+            winner,
+            session.proposal_count.get(),
+            self.leader(session_id).1,
+            total_votes,
+            outcome,
+        ))
+    }
+
+    /// @notice Require a minimum turnout and winning vote count for a session to pass
+    /// @dev Only the session creator or an ADMIN_ROLE holder, and only until the first vote is
+    ///      counted. A zero value disables that rule. Every enabled quorum must be met
+    /// @param session_id The session to configure
+    /// @param quorum_voters Minimum number of distinct voters with credits spent
+    /// @param quorum_credits Minimum total credits spent across all voters
+    /// @param vote_threshold Minimum net score the winning proposal needs
+    /// @return Result indicating success or specific error
+    pub fn set_session_rules(
+        &mut self,
+        session_id: U64,
+        quorum_voters: U64,
+        quorum_credits: U256,
+        vote_threshold: U64,
+    ) -> Result<(), QuadraticVotingError> {
+        self.only_session_manager(session_id, ADMIN_ROLE)?;
+        let session = self.sessions.get(session_id);
+        if session.cancelled.get() || session.finalized.get() || session.vote_sequence.get() != U64::ZERO {
+            return Err(QuadraticVotingError::InvalidSession(InvalidSession {}));
+        }
+
+        let mut session = self.sessions.setter(session_id);
+        session.quorum_voters.set(quorum_voters);
+        session.quorum_credits.set(quorum_credits);
+        session.vote_threshold.set(vote_threshold);
+        Ok(())
+    }
+
+    /// @notice Get a session's outcome rules and current turnout
+    /// @param session_id The session to query
+    /// @return Tuple containing (quorum_voters, quorum_credits, vote_threshold, voter_count, credits_spent, finalized)
+    pub fn get_session_rules(&self, session_id: U64) -> (U64, U256, U64, U64, U256, bool) {
+        let session = self.sessions.get(session_id);
+        (
+            session.quorum_voters.get(),
+            session.quorum_credits.get(),
+            session.vote_threshold.get(),
+            session.voter_count.get(),
+            session.total_credits_spent.get(),
+            session.finalized.get(),
+        )
+    }
+
+    /// @notice Freeze a session's outcome once voting is over
+    /// @dev Anyone can finalize after end_time and any reveal phase. While a TIE_BREAK_CREATOR tie
+    ///      for a seat or for the budget is unresolved, only the session creator or an ADMIN_ROLE holder can finalize, so the
+    ///      creator gets the chance to pick a winner first. Emits SessionFinalized and
+    ///      WinnersSelected, and freezes the budget allocation of budget sessions with
+    ///      BudgetAllocated. Finalized sessions cannot be cancelled, extended or have their tie
+    ///      resolved
+    /// @param session_id The session to finalize
+    /// @return The frozen outcome
+    pub fn finalize_session(&mut self, session_id: U64) -> Result<u8, QuadraticVotingError> {
+        let now = U256::from(self.vm().block_timestamp());
+        let session = self.sessions.get(session_id);
+        let voting_end = session.end_time.get() + U256::from(session.reveal_duration.get());
+        if session.creator.get() == Address::ZERO
+            || session.cancelled.get()
+            || session.finalized.get()
+            || now < voting_end
+        {
+            return Err(QuadraticVotingError::InvalidSession(InvalidSession {}));
+        }
+
+        let (outcome, winner) = self.current_outcome(session_id);
+        let (_, contested, open_seats) = self.seat_split(session_id);
+        let allocation = if session.budget_mode.get() {
+            Some(self.allocate_budget(session_id))
+        } else {
+            None
+        };
+        let budget_tied = allocation.as_ref().is_some_and(|(_, _, contested)| !contested.is_empty());
+        if session.tie_break.get().to::<u8>() == TIE_BREAK_CREATOR
+            && ((open_seats == 1 && !contested.contains(&session.tie_winner.get())) || budget_tied)
+        {
+            self.only_session_manager(session_id, ADMIN_ROLE)?;
+        }
+        let winners = self.select_winners(session_id);
+
+        // Store the rescanned leader so views stop rescanning once the session is settled
+        let (leader, leader_votes) = self.leader(session_id);
+
+        let mut session = self.sessions.setter(session_id);
+        session.leader.set(leader);
+        session.leader_votes.set(leader_votes);
+        session.leader_stale.set(false);
+        session.active.set(false);
+        session.finalized.set(true);
+        session.outcome.set(U8::from(outcome));
+        session.winner.set(winner);
+        if let Some((funded, remaining, _)) = &allocation {
+            for (index, &proposal_id) in funded.iter().enumerate() {
+                session.funded_ids.setter(U16::from(index + 1)).set(proposal_id);
+            }
+            session.funded_count.set(U16::from(funded.len()));
+            session.budget_remaining.set(*remaining);
+        }
+
+        log(
+            self.vm(),
+            SessionFinalized {
+                session_id: session_id.to::<u64>(),
+                outcome,
+                winner_id: winner.to::<u16>(),
+            },
+        );
+        log(
+            self.vm(),
+            WinnersSelected {
+                session_id: session_id.to::<u64>(),
+                proposal_ids: winners.iter().map(|id| id.to::<u16>()).collect(),
+            },
+        );
+        if let Some((funded, remaining, _)) = allocation {
+            log(
+                self.vm(),
+                BudgetAllocated {
+                    session_id: session_id.to::<u64>(),
+                    funded_ids: funded.iter().map(|id| id.to::<u16>()).collect(),
+                    remaining,
+                },
+            );
+        }
+        Ok(outcome)
+    }
+
+    /// @notice Get the proposals a session selects, best first
+    /// @dev Available once voting (and any reveal phase) is over. Takes up to winners_count
+    ///      proposals with a positive net score of at least vote_threshold, and none when quorum
+    ///      was missed. A tie across the last seats is settled by the tie-break policy: the lowest
+    ///      IDs or earliest proposals take them, the creator's resolve_tie pick takes a single
+    ///      contested seat, and otherwise the tied proposals are left out
+    /// @param session_id The session to query
+    /// @return The selected proposal IDs
+    pub fn get_winners(&self, session_id: U64) -> Result<Vec<U16>, QuadraticVotingError> {
+        let now = U256::from(self.vm().block_timestamp());
+        let session = self.sessions.get(session_id);
+        let voting_end = session.end_time.get() + U256::from(session.reveal_duration.get());
+        if session.creator.get() == Address::ZERO || session.cancelled.get() || now < voting_end {
+            return Err(QuadraticVotingError::InvalidSession(InvalidSession {}));
+        }
+        Ok(self.select_winners(session_id))
+    }
+
+    /// @notice Get every proposal ranked by net score (support minus opposition)
+    /// @dev Proposals with equal scores form a tie group and share a rank, and the next group's
+    ///      rank skips past them (1, 1, 3). Within a group, entries are ordered by the session's
+    ///      tie-break policy: earliest to reach the score first under TIE_BREAK_EARLIEST, lowest
+    ///      ID first otherwise. Proposals rejected by moderation are left out
+    /// @param session_id The session to rank
+    /// @return Array of tuples containing (proposal_id, net_score, rank)
+    pub fn get_leaderboard(&self, session_id: U64) -> Result<Vec<(U16, I128, U16)>, QuadraticVotingError> {
+        let session = self.sessions.get(session_id);
+        if session.creator.get() == Address::ZERO || session.cancelled.get() {
+            return Err(QuadraticVotingError::InvalidSession(InvalidSession {}));
+        }
+
+        let ranked = self.ranked_proposals(session_id);
+        let mut leaderboard = Vec::with_capacity(ranked.len());
+        let mut rank = U16::ZERO;
+        for (i, &(proposal_id, net_score, _)) in ranked.iter().enumerate() {
+            if i == 0 || net_score != ranked[i - 1].1 {
+                rank = U16::from(i + 1);
+            }
+            leaderboard.push((proposal_id, I128::unchecked_from(net_score), rank));
+        }
+        Ok(leaderboard)
+    }
+
+    /// @notice Choose how a tie for the most votes is settled
+    /// @dev Only the session creator or an ADMIN_ROLE holder, and only until the first vote is
+    ///      counted, so the policy cannot be picked with the standings in view
+    /// @param session_id The session to configure
+    /// @param policy TIE_BREAK_LOWEST_ID, TIE_BREAK_EARLIEST, TIE_BREAK_CREATOR or TIE_BREAK_NONE
+    /// @return Result indicating success or specific error
+    pub fn set_tie_break(&mut self, session_id: U64, policy: u8) -> Result<(), QuadraticVotingError> {
+        self.only_session_manager(session_id, ADMIN_ROLE)?;
+        if policy > TIE_BREAK_NONE {
+            return Err(QuadraticVotingError::InvalidMode(InvalidMode {}));
+        }
+        let session = self.sessions.get(session_id);
+        if session.cancelled.get() || session.vote_sequence.get() != U64::ZERO {
+            return Err(QuadraticVotingError::InvalidSession(InvalidSession {}));
+        }
+
+        self.sessions.setter(session_id).tie_break.set(U8::from(policy));
+        Ok(())
+    }
+
+    /// @notice Pick the winner among proposals tied for the last open seat
+    /// @dev Only the session creator or an ADMIN_ROLE holder, in sessions using TIE_BREAK_CREATOR,
+    ///      once voting (and any reveal phase) is over. In single-winner sessions the seat is the
+    ///      top spot. Ties across several open seats cannot be resolved. In budget sessions the pick
+    ///      can instead be one of the tied proposals contesting the budget, which is then funded
+    ///      first. A session holds one pick, and the choice lapses if reopening the session changes
+    ///      the standings
+    /// @param session_id The session with the tie
+    /// @param proposal_id One of the tied proposals
+    /// @return Result indicating success or specific error
+    pub fn resolve_tie(&mut self, session_id: U64, proposal_id: U16) -> Result<(), QuadraticVotingError> {
+        self.only_session_manager(session_id, ADMIN_ROLE)?;
+
+        let now = U256::from(self.vm().block_timestamp());
+        let session = self.sessions.get(session_id);
+        let voting_end = session.end_time.get() + U256::from(session.reveal_duration.get());
+        if session.cancelled.get()
+            || session.finalized.get()
+            || session.tie_break.get().to::<u8>() != TIE_BREAK_CREATOR
+            || now < voting_end
+        {
+            return Err(QuadraticVotingError::InvalidSession(InvalidSession {}));
+        }
+
+        let (_, contested, open_seats) = self.seat_split(session_id);
+        let budget_contested = if session.budget_mode.get() {
+            self.allocate_budget(session_id).2
+        } else {
+            Vec::new()
+        };
+        let seat_tie = open_seats == 1 && contested.contains(&proposal_id);
+        if !seat_tie && !budget_contested.contains(&proposal_id) {
+            return Err(QuadraticVotingError::InvalidProposal(InvalidProposal {}));
+        }
+
+        self.sessions.setter(session_id).tie_winner.set(proposal_id);
+        log(
+            self.vm(),
+            TieResolved {
+                session_id: session_id.to::<u64>(),
+                proposal_id: proposal_id.to::<u16>(),
+            },
+        );
+        Ok(())
+    }
+
+    /// @notice Get a session's tie-break policy
+    /// @param session_id The session to query
+    /// @return Tuple containing (policy, proposal picked with resolve_tie or 0)
+    pub fn get_tie_break(&self, session_id: U64) -> (u8, U16) {
+        let session = self.sessions.get(session_id);
+        (session.tie_break.get().to::<u8>(), session.tie_winner.get())
+    }
+
+    /// @notice Get a page of a session's proposals with their current vote counts
+    /// @dev Covers proposal IDs offset + 1 through offset + limit. Proposals rejected by moderation
+    ///      are left out, so a page can hold fewer than `limit` entries; page through until offset
+    ///      reaches the proposal count from get_session
+    /// @param session_id The session to query
+    /// @param offset Number of proposal IDs to skip
+    /// @param limit Maximum number of proposal IDs to cover
+    /// @return Array of tuples containing (proposal_id, title, description, votes_for, votes_against, net_score)
+    pub fn get_session_proposals(
+        &self,
+        session_id: U64,
+        offset: U16,
+        limit: U16,
+    ) -> Result<Vec<ProposalInfo>, QuadraticVotingError> {
+        let session = self.sessions.get(session_id);
+        if session.creator.get() == Address::ZERO {
+            return Err(QuadraticVotingError::InvalidSession(InvalidSession {}));
+        }
+
+        let proposal_count = session.proposal_count.get().to::<u32>();
+        let first = offset.to::<u32>() + 1;
+        let last = (offset.to::<u32>() + limit.to::<u32>()).min(proposal_count);
+
+        let mut proposals = Vec::with_capacity(last.saturating_sub(first - 1) as usize);
+        for i in first..=last {
+            let id = U16::from(i);
+            let proposal = session.proposals.get(id);
+            if proposal.rejected.get() {
+                continue;
+            }
+
+            proposals.push((
+                id,
+                proposal.title.get_string(),
+                proposal.description.get_string(),
+                proposal.vote_count.get(),
+                proposal.against_count.get(),
+                I128::unchecked_from(proposal.net_score()),
+            ));
+        }
+
+        Ok(proposals)
+    }
+
+    /// @notice Check whether an account holds a role
+    /// @param role The role identifier (ADMIN_ROLE is bytes32(0))
+    /// @param account The account to check
+    /// @return True if the account holds the role
+    pub fn has_role(&self, role: B256, account: Address) -> bool {
+        self.roles.get(role).get(account)
+    }
+
+    /// @notice Grant a role to an account
+    /// @dev Requires ADMIN_ROLE. ADMIN_ROLE itself can only move through transfer_admin
+    /// @param role The role to grant
+    /// @param account The account receiving the role
+    /// @return Result indicating success or specific error
+    pub fn grant_role(&mut self, role: B256, account: Address) -> Result<(), QuadraticVotingError> {
+        self.only_role(ADMIN_ROLE)?;
+        if role == ADMIN_ROLE {
+            return Err(QuadraticVotingError::Unauthorized(Unauthorized {}));
+        }
+        self.set_role(role, account, true);
+        Ok(())
+    }
+
+    /// @notice Revoke a role from an account
+    /// @dev Requires ADMIN_ROLE. ADMIN_ROLE itself can only move through transfer_admin
+    /// @param role The role to revoke
+    /// @param account The account losing the role
+    /// @return Result indicating success or specific error
+    pub fn revoke_role(&mut self, role: B256, account: Address) -> Result<(), QuadraticVotingError> {
+        self.only_role(ADMIN_ROLE)?;
+        if role == ADMIN_ROLE {
+            return Err(QuadraticVotingError::Unauthorized(Unauthorized {}));
+        }
+        self.set_role(role, account, false);
+        Ok(())
+    }
+
+    /// @notice Give up a role held by the caller
+    /// @dev ADMIN_ROLE cannot be renounced, only transferred, so the contract is never left without an admin
+    /// @param role The role to renounce
+    /// @return Result indicating success or specific error
+    pub fn renounce_role(&mut self, role: B256) -> Result<(), QuadraticVotingError> {
+        self.only_role(role)?;
+        if role == ADMIN_ROLE {
+            return Err(QuadraticVotingError::Unauthorized(Unauthorized {}));
+        }
+        let caller = self.vm().msg_sender();
+        self.set_role(role, caller, false);
+        Ok(())
+    }
+
+    /// @notice Start transferring the admin role to a new address
+    /// @dev Requires ADMIN_ROLE. The transfer completes when the new admin calls accept_admin.
+    ///      Calling again replaces the pending admin; Address::ZERO cancels the transfer
+    /// @param new_admin The proposed admin
+    /// @return Result indicating success or specific error
+    pub fn transfer_admin(&mut self, new_admin: Address) -> Result<(), QuadraticVotingError> {
+        self.only_role(ADMIN_ROLE)?;
+        self.pending_admin.set(new_admin);
+
+        log(
+            self.vm(),
+            AdminTransferStarted {
+                current_admin: self.admin.get(),
+                pending_admin: new_admin,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// @notice Accept a pending admin transfer
+    /// @dev Moves ADMIN_ROLE from the current admin to the caller. Other roles are left untouched
+    /// @return Result indicating success or specific error
+    pub fn accept_admin(&mut self) -> Result<(), QuadraticVotingError> {
+        let caller = self.vm().msg_sender();
+        if caller == Address::ZERO || caller != self.pending_admin.get() {
+            return Err(QuadraticVotingError::Unauthorized(Unauthorized {}));
+        }
+
+        let previous_admin = self.admin.get();
+        self.set_role(ADMIN_ROLE, previous_admin, false);
+        self.set_role(ADMIN_ROLE, caller, true);
+        self.admin.set(caller);
+        self.pending_admin.set(Address::ZERO);
+
+        Ok(())
+    }
+
+    /// @notice Get the current and pending admin
+    /// @return Tuple containing (admin, pending_admin)
+    pub fn get_admin(&self) -> (Address, Address) {
+        (self.admin.get(), self.pending_admin.get())
+    }
+
+
+
+}
+
+impl QuadraticVoting {
+    /// @dev Single authorization guard: fails with Unauthorized unless the caller holds `role`
+    /// @param role The role required for the call
+    fn only_role(&self, role: B256) -> Result<(), QuadraticVotingError> {
+        if !self.roles.get(role).get(self.vm().msg_sender()) {
+            return Err(QuadraticVotingError::Unauthorized(Unauthorized {}));
+        }
+        Ok(())
+    }
+
+    /// @dev Ensures the session exists and the caller is its creator or holds `role`
+    /// @param session_id The session being managed
+    /// @param role The role that may manage any session
+    fn only_session_manager(&self, session_id: U64, role: B256) -> Result<(), QuadraticVotingError> {
+        let creator = self.sessions.get(session_id).creator.get();
+        if creator == Address::ZERO {
+            return Err(QuadraticVotingError::InvalidSession(InvalidSession {}));
+        }
+
+        if self.vm().msg_sender() == creator {
+            return Ok(());
+        }
+        self.only_role(role)
+    }
+
+    /// @dev Writes a role membership and emits RoleGranted / RoleRevoked when it changes
+    /// @param role The role to update
+    /// @param account The account whose membership changes
+    /// @param granted Whether the account should hold the role
+    fn set_role(&mut self, role: B256, account: Address, granted: bool) {
+        if self.roles.get(role).get(account) == granted {
+            return;
+        }
+        self.roles.setter(role).setter(account).set(granted);
+
+        let sender = self.vm().msg_sender();
+        if granted {
+            log(self.vm(), RoleGranted { role, account, sender });
+        } else {
+            log(self.vm(), RoleRevoked { role, account, sender });
+        }
+    }
+
+    /// @dev Ensures the session exists and the voter may take part in it
+    /// @param session_id The session being voted in
+    /// @param voter The account voting
+    fn check_voter(&self, session_id: U64, voter: Address) -> Result<(), QuadraticVotingError> {
+        let session = self.sessions.get(session_id);
+        if session.creator.get() == Address::ZERO {
+            return Err(QuadraticVotingError::InvalidSession(InvalidSession {}));
+        }
+        // Merkle-gated sessions admit voters by proof instead of registration
+        let eligible = if session.eligibility_root.get() == B256::ZERO {
+            self.voters.get(voter).is_registered.get()
+        } else {
+            session.has_allocation.get(voter)
+        };
+        if !eligible {
+            return Err(QuadraticVotingError::VoterNotRegistered(VoterNotRegistered {}));
+        }
+        if self.voters.get(voter).is_suspended.get() {
+            return Err(QuadraticVotingError::Unauthorized(Unauthorized {}));
+        }
+        Ok(())
+    }
+
+    /// @dev Calls an ERC-20 transfer function, requiring it to succeed and return true
+    /// @param token The token contract
+    /// @param calldata Encoded transfer or transferFrom call
+    #[cfg(any(feature = "quadratic-funding", feature = "token-credits"))]
+    fn call_token(&mut self, token: Address, calldata: Vec<u8>) -> Result<(), QuadraticVotingError> {
+        let returned = self
+            .vm()
+            .call(&self, token, &calldata)
+            .map_err(|_| QuadraticVotingError::TokenTransferFailed(TokenTransferFailed {}))?;
+        match <sol_data::Bool as SolType>::abi_decode(&returned, true) {
+            Ok(true) => Ok(()),
+            _ => Err(QuadraticVotingError::TokenTransferFailed(TokenTransferFailed {})),
+        }
+    }
+
+    /// @dev Recovers the signer of a digest through the ecrecover precompile. Rejects malformed
+    ///      and high-s (malleable) signatures, and signatures that recover to no address
+    /// @param digest The signed EIP-712 digest
+    /// @param signature 65-byte r || s || v signature, v in {27, 28}
+    fn recover_signer(&self, digest: B256, signature: &[u8]) -> Result<Address, QuadraticVotingError> {
+        if signature.len() != eip712::SIGNATURE_LENGTH {
+            return Err(QuadraticVotingError::InvalidSignature(InvalidSignature {}));
+        }
+        let v = signature[64];
+        let s = U256::from_be_slice(&signature[32..64]);
+        if (v != 27 && v != 28) || s > SECP256K1_HALF_ORDER {
+            return Err(QuadraticVotingError::InvalidSignature(InvalidSignature {}));
+        }
+
+        let mut input = [0u8; 128];
+        input[..32].copy_from_slice(digest.as_slice());
+        input[63] = v;
+        input[64..].copy_from_slice(&signature[..64]);
+
+        let output = self
+            .vm()
+            .static_call(&self, ECRECOVER, &input)
+            .map_err(|_| QuadraticVotingError::InvalidSignature(InvalidSignature {}))?;
+        if output.len() != 32 {
+            return Err(QuadraticVotingError::InvalidSignature(InvalidSignature {}));
+        }
+        let signer = Address::from_slice(&output[12..]);
+        if signer == Address::ZERO {
+            return Err(QuadraticVotingError::InvalidSignature(InvalidSignature {}));
+        }
+        Ok(signer)
+    }
+
+    /// @dev Voters who delegated must undelegate before voting themselves
+    /// @param session_id The session being voted in
+    /// @param voter The account voting on its own behalf
+    fn check_not_delegated(&self, session_id: U64, voter: Address) -> Result<(), QuadraticVotingError> {
+        if self.sessions.get(session_id).delegates.get(voter) != Address::ZERO {
+            return Err(QuadraticVotingError::Unauthorized(Unauthorized {}));
+        }
+        Ok(())
+    }
+
+    /// @dev Follows a delegation chain to the address that may vote for `voter`.
+    ///      Returns Address::ZERO if the voter has not delegated. delegate keeps every chain
+    ///      within MAX_DELEGATION_DEPTH, which also bounds the walk
+    /// @param session_id The session the delegation belongs to
+    /// @param voter The delegator
+    fn resolve_delegate(&self, session_id: U64, voter: Address) -> Address {
+        let session = self.sessions.get(session_id);
+        let mut current = session.delegates.get(voter);
+        if current == Address::ZERO {
+            return Address::ZERO;
+        }
+        for _ in 0..MAX_DELEGATION_DEPTH {
+            let next = session.delegates.get(current);
+            if next == Address::ZERO {
+                return current;
+            }
+            current = next;
+        }
+        Address::ZERO
+    }
+
+    /// @dev Adds or removes the delegation link `delegator -> to` in the per-address heights, the
+    ///      length of the longest delegation chain ending at each address. Each address counts
+    ///      its direct delegators by height, so a removed link can lower a height without
+    ///      revisiting other delegators. Changes travel down the chain after `to`, which is at
+    ///      most MAX_DELEGATION_DEPTH links long
+    /// @param session_id The session the delegation belongs to
+    /// @param delegator The address delegating
+    /// @param to Its delegate
+    /// @param added Whether the link is being added or removed
+    fn update_delegation_heights(&mut self, session_id: U64, delegator: Address, to: Address, added: bool) {
+        let mut session = self.sessions.setter(session_id);
+        let height = session.delegation_height.get(delegator);
+        let (mut removed_height, mut added_height) = if added { (None, Some(height)) } else { (Some(height), None) };
+        let mut node = to;
+        loop {
+            let mut counts = session.delegators_at_height.setter(node);
+            if let Some(height) = removed_height {
+                let count = counts.get(height);
+                counts.setter(height).set(count - U32::from(1));
+            }
+            if let Some(height) = added_height {
+                let count = counts.get(height);
+                counts.setter(height).set(count + U32::from(1));
+            }
+
+            let old_height = session.delegation_height.get(node);
+            let new_height = (0..MAX_DELEGATION_DEPTH as u8)
+                .rev()
+                .find(|&height| session.delegators_at_height.get(node).get(U8::from(height)) > U32::ZERO)
+                .map_or(U8::ZERO, |height| U8::from(height + 1));
+            session.delegation_height.setter(node).set(new_height);
+            let next = session.delegates.get(node);
+            if new_height == old_height || next == Address::ZERO {
+                return;
+            }
+            (removed_height, added_height) = (Some(old_height), Some(new_height));
+            node = next;
+        }
+    }
+
+    /// @dev Ensures the session is running and inside its voting window
+    /// @param session_id The session being voted in
+    fn check_voting_open(&self, session_id: U64) -> Result<(), QuadraticVotingError> {
+        let session = self.sessions.get(session_id);
+        if !session.active.get() || session.paused.get() {
+            return Err(QuadraticVotingError::InvalidSession(InvalidSession {}));
+        }
+        let now = U256::from(self.vm().block_timestamp());
+        if now < session.start_time.get() || now >= session.end_time.get() {
+            return Err(QuadraticVotingError::InvalidSession(InvalidSession {}));
+        }
+        Ok(())
+    }
+
+    /// @dev Shared vote validation for every public voting entrypoint
+    /// @param session_id The session to vote in
+    /// @param voter The account whose credits and votes are updated
+    /// @param proposal_ids Array of proposal IDs to vote for
+    /// @param vote_counts Corresponding vote intensities
+    /// @param against Whether each vote opposes its proposal; empty when every vote supports
+    fn cast_votes(
+        &mut self,
+        session_id: U64,
+        voter: Address,
+        proposal_ids: Vec<U16>,
+        vote_counts: Vec<U64>,
+        against: Vec<bool>,
+    ) -> Result<(), QuadraticVotingError> {
+        self.check_voter(session_id, voter)?;
+        self.check_voting_open(session_id)?;
+        // Commit-reveal sessions only accept votes through reveal_vote
+        if self.sessions.get(session_id).reveal_duration.get() != U64::ZERO {
+            return Err(QuadraticVotingError::InvalidSession(InvalidSession {}));
+        }
+
+        self.tally_votes(session_id, voter, proposal_ids, vote_counts, against)
+    }
+
+    /// @dev Applies a validated voter's new intensities, charging or refunding the quadratic cost difference
+    /// @param session_id The session to vote in
+    /// @param voter The account whose credits and votes are updated
+    /// @param proposal_ids Array of proposal IDs to vote on
+    /// @param vote_counts Corresponding vote intensities
+    /// @param against Whether each vote opposes its proposal; empty when every vote supports
+    fn tally_votes(
+        &mut self,
+        session_id: U64,
+        voter: Address,
+        proposal_ids: Vec<U16>,
+        vote_counts: Vec<U64>,
+        against: Vec<bool>,
+    ) -> Result<(), QuadraticVotingError> {
+        if proposal_ids.len() != vote_counts.len() || !(against.is_empty() || against.len() == vote_counts.len()) {
+            return Err(QuadraticVotingError::InvalidVoteCount(InvalidVoteCount {}));
+        }
+
+        let session_data = self.sessions.get(session_id);
+
+        // Re-voting replaces the voter's previous intensity and direction, so only the
+        // difference between the old and new quadratic cost is charged or refunded
+        let mut previous_cost = U256::ZERO;
+        let mut new_cost = U256::ZERO;
+        let proposal_count = session_data.proposal_count.get();
+        let mut vote_updates = Vec::new();
+        for (i, (&proposal_id, &vote_count)) in proposal_ids.iter().zip(vote_counts.iter()).enumerate() {
+            if proposal_id == U16::ZERO
+                || proposal_id > proposal_count
+                || proposal_ids[..i].contains(&proposal_id)
+                || session_data.proposals.get(proposal_id).rejected.get()
+            {
+                return Err(QuadraticVotingError::InvalidProposal(InvalidProposal {}));
+            }
+
+            let current_votes = session_data.votes_per_proposal.get(voter).get(proposal_id);
+            let currently_opposes = session_data.opposes.get(voter).get(proposal_id);
+            let opposes = against.get(i).copied().unwrap_or(false) && vote_count > U64::ZERO;
+            let proposal = session_data.proposals.get(proposal_id);
+            let old_tallies = (proposal.vote_count.get(), proposal.against_count.get());
+
+            previous_cost = previous_cost
+                .checked_add(quadratic_cost(current_votes))
+                .ok_or(QuadraticVotingError::InsufficientCredits(InsufficientCredits {}))?;
+            new_cost = new_cost
+                .checked_add(quadratic_cost(vote_count))
+                .ok_or(QuadraticVotingError::InsufficientCredits(InsufficientCredits {}))?;
+
+            // Take the previous vote off the tally it counted towards, then add the new one
+            let (mut support, mut opposition) = old_tallies;
+            if currently_opposes {
+                opposition = opposition.saturating_sub(current_votes);
+            } else {
+                support = support.saturating_sub(current_votes);
+            }
+            let tally = if opposes { &mut opposition } else { &mut support };
+            *tally = tally
+                .checked_add(vote_count)
+                .ok_or(QuadraticVotingError::InvalidVoteCount(InvalidVoteCount {}))?;
+
+            vote_updates.push((proposal_id, vote_count, opposes, old_tallies, (support, opposition)));
+        }
+
+        let credits_spent = session_data.credits_spent.get(voter);
+        let credits_allocated = session_data.allocation_of(voter);
+        let total_spent = credits_spent
+            .saturating_sub(previous_cost)
+            .checked_add(new_cost)
+            .ok_or(QuadraticVotingError::InsufficientCredits(InsufficientCredits {}))?;
+        if total_spent > credits_allocated {
+            return Err(QuadraticVotingError::InsufficientCredits(InsufficientCredits {}));
+        }
+
+        // Turnout counts voters whose standing votes cost credits, and the credits those votes hold
+        let mut voter_count = session_data.voter_count.get();
+        if credits_spent.is_zero() && !total_spent.is_zero() {
+            voter_count += U64::from(1);
+        } else if !credits_spent.is_zero() && total_spent.is_zero() {
+            voter_count -= U64::from(1);
+        }
+        let total_credits_spent = session_data.total_credits_spent.get() - credits_spent + total_spent;
+
+        let mut leader = session_data.leader.get();
+        let mut leader_votes = session_data.leader_votes.get();
+        let mut total_votes = session_data.total_votes.get();
+        let mut leader_stale = session_data.leader_stale.get();
+        for &(proposal_id, _, _, (old_support, old_opposition), (support, opposition)) in &vote_updates {
+            total_votes = total_votes - U256::from(old_support) - U256::from(old_opposition)
+                + U256::from(support)
+                + U256::from(opposition);
+            let score = lead_score(support, opposition);
+            if score > leader_votes || (score == leader_votes && score > U64::ZERO && proposal_id < leader) {
+                leader = proposal_id;
+                leader_votes = score;
+                leader_stale = false;
+            } else if proposal_id == leader {
+                leader_stale = score < leader_votes;
+            }
+        }
+
+        // Each change to a proposal's net score gets the next sequence number, so equal scores
+        // can be ordered by which proposal reached them first
+        let mut vote_sequence = session_data.vote_sequence.get();
+        for (proposal_id, new_votes, opposes, _, (support, opposition)) in vote_updates {
+            let mut session = self.sessions.setter(session_id);
+            let mut proposal = session.proposals.setter(proposal_id);
+            let old_net = proposal.net_score();
+            proposal.vote_count.set(support);
+            proposal.against_count.set(opposition);
+            if proposal.net_score() != old_net {
+                vote_sequence += U64::from(1);
+                proposal.reached_at.set(vote_sequence);
+            }
+
+            let mut session = self.sessions.setter(session_id);
+            session.votes_per_proposal.setter(voter).setter(proposal_id).set(new_votes);
+            session.opposes.setter(voter).setter(proposal_id).set(opposes);
+        }
+
+        self.sessions
+            .setter(session_id)
+            .credits_spent
+            .setter(voter)
+            .set(total_spent);
+
+        // Only a leader losing votes can hand the lead to a proposal outside this batch. Rather
+        // than rescanning every proposal here, the tracked leader is marked stale and the views
+        // that need it rescan instead, so a vote only costs work proportional to its batch.
+        // While stale, leader_votes keeps the old top score, which no proposal exceeds, and every
+        // proposal below the tracked leader's ID stays under it. The lead is certain again once
+        // a proposal takes it over or the tracked leader gets back to that score
+        let mut session = self.sessions.setter(session_id);
+        session.leader_stale.set(leader_stale);
+        session.leader.set(leader);
+        session.leader_votes.set(leader_votes);
+        session.total_votes.set(total_votes);
+        session.vote_sequence.set(vote_sequence);
+        session.voter_count.set(voter_count);
+        session.total_credits_spent.set(total_credits_spent);
+
+        log(
+            self.vm(),
+            VoteCast {
+                session_id: session_id.as_limbs()[0],
+                voter,
+                total_credits_spent: new_cost,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// @dev Returns the tracked leader, or rescans the proposals once a vote has taken net score
+    ///      away from the leader. The scan lasts until a later vote settles the lead again or
+    ///      finalize_session stores its result, so it is only called from views and finalize_session
+    /// @param session_id The session to query
+    /// @return Tuple containing (leader, leader_score)
+    fn leader(&self, session_id: U64) -> (U16, U64) {
+        let session = self.sessions.get(session_id);
+        if session.leader_stale.get() {
+            return self.find_leader(session_id);
+        }
+        (session.leader.get(), session.leader_votes.get())
+    }
+
+    /// @dev Scans every proposal for the one with the highest positive net score, lowest ID first
+    ///      on ties. Returns (0, 0) when no proposal has more support than opposition
+    /// @param session_id The session to scan
+    fn find_leader(&self, session_id: U64) -> (U16, U64) {
+        let session = self.sessions.get(session_id);
+        let mut leader = U16::ZERO;
+        let mut leader_votes = U64::ZERO;
+        for i in 1..=session.proposal_count.get().to::<u16>() {
+            let proposal_id = U16::from(i);
+            let proposal = session.proposals.get(proposal_id);
+            let score = lead_score(proposal.vote_count.get(), proposal.against_count.get());
+            if score > leader_votes {
+                leader = proposal_id;
+                leader_votes = score;
+            }
+        }
+        (leader, leader_votes)
+    }
+
+    /// @dev Every non-rejected proposal as (proposal_id, net_score, reached_at), highest net score
+    ///      first and then in the session's tie-break order
+    /// @param session_id The session to rank
+    fn ranked_proposals(&self, session_id: U64) -> Vec<(U16, i128, U64)> {
+        let session = self.sessions.get(session_id);
+        let mut ranked = Vec::new();
+        for i in 1..=session.proposal_count.get().to::<u16>() {
+            let proposal_id = U16::from(i);
+            let proposal = session.proposals.get(proposal_id);
+            if !proposal.rejected.get() {
+                ranked.push((proposal_id, proposal.net_score(), proposal.reached_at.get()));
+            }
+        }
+
+        let by_earliest = session.tie_break.get().to::<u8>() == TIE_BREAK_EARLIEST;
+        ranked.sort_unstable_by(|a, b| {
+            let order = b.1.cmp(&a.1);
+            let order = if by_earliest { order.then(a.2.cmp(&b.2)) } else { order };
+            order.then(a.0.cmp(&b.0))
+        });
+        ranked
+    }
+
+    /// @dev Splits the proposals that qualify to win (positive net score, at least vote_threshold)
+    ///      around the session's winners_count seats. A tie group straddling the last seat is
+    ///      contested; the proposals ranked above it are seated
+    /// @param session_id The session to split
+    /// @return Tuple containing (seated, contested in tie-break order, seats left for the contested group)
+    fn seat_split(&self, session_id: U64) -> (Vec<U16>, Vec<U16>, usize) {
+        let session = self.sessions.get(session_id);
+        let threshold = session.vote_threshold.get().to::<u64>() as i128;
+        let seats = session.winners_count.get().to::<usize>();
+        let qualified: Vec<(U16, i128)> = self
+            .ranked_proposals(session_id)
+            .into_iter()
+            .take_while(|&(_, net_score, _)| net_score > 0 && net_score >= threshold)
+            .map(|(proposal_id, net_score, _)| (proposal_id, net_score))
+            .collect();
+
+        if qualified.len() <= seats || qualified[seats].1 != qualified[seats - 1].1 {
+            let seated = qualified.iter().take(seats).map(|&(proposal_id, _)| proposal_id).collect();
+            return (seated, Vec::new(), 0);
+        }
+
+        let cutoff = qualified[seats - 1].1;
+        let (seated, contested): (Vec<_>, Vec<_>) = qualified
+            .into_iter()
+            .filter(|&(_, net_score)| net_score >= cutoff)
+            .partition(|&(_, net_score)| net_score > cutoff);
+        let open_seats = seats - seated.len();
+        (
+            seated.into_iter().map(|(proposal_id, _)| proposal_id).collect(),
+            contested.into_iter().map(|(proposal_id, _)| proposal_id).collect(),
+            open_seats,
+        )
+    }
+
+    /// @dev The proposals the session currently selects, best first, see get_winners
+    /// @param session_id The session to decide
+    fn select_winners(&self, session_id: U64) -> Vec<U16> {
+        let session = self.sessions.get(session_id);
+        if session.voter_count.get() < session.quorum_voters.get()
+            || session.total_credits_spent.get() < session.quorum_credits.get()
+        {
+            return Vec::new();
+        }
+
+        let (mut winners, contested, open_seats) = self.seat_split(session_id);
+        match session.tie_break.get().to::<u8>() {
+            TIE_BREAK_LOWEST_ID | TIE_BREAK_EARLIEST => winners.extend(contested.into_iter().take(open_seats)),
+            TIE_BREAK_CREATOR if open_seats == 1 && contested.contains(&session.tie_winner.get()) => {
+                winners.push(session.tie_winner.get())
+            }
+            _ => {}
+        }
+        winners
+    }
+
+    /// @dev Walks proposals from the highest net score down, in tie-break order, funding each one
+    ///      whose requested amount still fits the remaining budget. Proposals that do not fit are
+    ///      skipped, so cheaper ones further down can still be funded. Only proposals that could
+    ///      win (positive net score of at least vote_threshold) are funded, and none without quorum.
+    ///      Tied proposals that each fit but not together are contested: the lowest-ID and earliest
+    ///      policies settle their order, TIE_BREAK_CREATOR funds the resolve_tie pick first, and
+    ///      otherwise allocation stops there and leaves the rest of the budget unspent
+    /// @param session_id The budget session to allocate
+    /// @return Tuple containing (funded_ids, remaining_budget, unsettled contested proposal IDs)
+    fn allocate_budget(&self, session_id: U64) -> (Vec<U16>, U256, Vec<U16>) {
+        let session = self.sessions.get(session_id);
+        let mut remaining = session.budget.get();
+        let mut funded = Vec::new();
+        if session.voter_count.get() < session.quorum_voters.get()
+            || session.total_credits_spent.get() < session.quorum_credits.get()
+        {
+            return (funded, remaining, Vec::new());
+        }
+
+        let threshold = session.vote_threshold.get().to::<u64>() as i128;
+        let ranked: Vec<(U16, i128)> = self
+            .ranked_proposals(session_id)
+            .into_iter()
+            .take_while(|&(_, net_score, _)| net_score > 0 && net_score >= threshold)
+            .map(|(proposal_id, net_score, _)| (proposal_id, net_score))
+            .collect();
+
+        let mut start = 0;
+        while start < ranked.len() && !remaining.is_zero() {
+            let score = ranked[start].1;
+            let end = start + ranked[start..].iter().take_while(|&&(_, net_score)| net_score == score).count();
+            let mut group: Vec<(U16, U256)> = ranked[start..end]
+                .iter()
+                .map(|&(proposal_id, _)| (proposal_id, session.proposals.get(proposal_id).requested_amount.get()))
+                .filter(|&(_, requested)| requested <= remaining)
+                .collect();
+            start = end;
+
+            let total = group.iter().fold(U256::ZERO, |sum, &(_, requested)| sum.saturating_add(requested));
+            if group.len() > 1 && total > remaining {
+                let tie_winner = session.tie_winner.get();
+                match session.tie_break.get().to::<u8>() {
+                    TIE_BREAK_LOWEST_ID | TIE_BREAK_EARLIEST => {}
+                    TIE_BREAK_CREATOR if group.iter().any(|&(proposal_id, _)| proposal_id == tie_winner) => {
+                        group.sort_by_key(|&(proposal_id, _)| proposal_id != tie_winner);
+                    }
+                    _ => {
+                        let contested = group.into_iter().map(|(proposal_id, _)| proposal_id).collect();
+                        return (funded, remaining, contested);
+                    }
+                }
+            }
+
+            for (proposal_id, requested) in group {
+                if requested <= remaining {
+                    remaining -= requested;
+                    funded.push(proposal_id);
+                }
+            }
+        }
+        (funded, remaining, Vec::new())
+    }
+
+    /// @dev Applies the session's quorum, threshold and tie-break rules to its current standings
+    /// @param session_id The session to decide
+    /// @return Tuple containing (outcome, winner), where the winner is 0 unless the outcome is OUTCOME_PASSED
+    fn current_outcome(&self, session_id: U64) -> (u8, U16) {
+        let session = self.sessions.get(session_id);
+        if session.voter_count.get() < session.quorum_voters.get()
+            || session.total_credits_spent.get() < session.quorum_credits.get()
+        {
+            return (OUTCOME_FAILED_QUORUM, U16::ZERO);
+        }
+
+        let (leader, top_votes) = self.leader(session_id);
+        if top_votes == U64::ZERO || top_votes < session.vote_threshold.get() {
+            return (OUTCOME_NO_WINNER, U16::ZERO);
+        }
+
+        // The tracked leader already is the lowest ID among the top proposals
+        if session.winners_count.get() == U16::from(1) && session.tie_break.get().to::<u8>() == TIE_BREAK_LOWEST_ID {
+            return (OUTCOME_PASSED, leader);
+        }
+
+        // The top proposal qualifies, so no winners means a tie for the top seats was left unsettled
+        match self.select_winners(session_id).first() {
+            Some(&winner) => (OUTCOME_PASSED, winner),
+            None => (OUTCOME_TIED, U16::ZERO),
+        }
+    }
+
+    /// @dev Marks a voter as registered, clearing any pending request. No-op if already registered
+    /// @param voter The voter to admit
+    fn admit_voter(&mut self, voter: Address) {
+        if self.voters.get(voter).is_registered.get() {
+            return;
+        }
+
+        let mut record = self.voters.setter(voter);
+        record.is_pending.set(false);
+        record.is_registered.set(true);
+        let identity = record.identity.get();
+
+        log(self.vm(), VoterRegistered { voter, identity });
+    }
+
+    /// @dev Shared implementation of pause_session and resume_session
+    /// @param session_id The session to update
+    /// @param paused The new paused flag; must differ from the current one
+    fn set_session_paused(
+        &mut self,
+        session_id: U64,
+        paused: bool,
+    ) -> Result<(), QuadraticVotingError> {
+        self.only_session_manager(session_id, PAUSER_ROLE)?;
+        let session = self.sessions.get(session_id);
+        if !session.active.get() || session.paused.get() == paused {
+            return Err(QuadraticVotingError::InvalidSession(InvalidSession {}));
+        }
+
+        self.sessions.setter(session_id).paused.set(paused);
+
+        log(
+            self.vm(),
+            SessionPaused {
+                id: session_id.as_limbs()[0],
+                paused,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// @dev Internal helper to add proposals to a session at creation or during its proposal phase
+    /// @param session_id The session to add proposals to
+    /// @param proposals Array of (title, description) tuples
+    fn add_proposals_to_session(
+        &mut self,
+        session_id: U64,
+        proposals: Vec<(String, String)>,
+    ) -> Result<(), QuadraticVotingError> {
+        check_proposals(&proposals)?;
+
         for (title, description) in proposals {
-            let proposal_id = self.sessions.getter(session_id).proposal_count.get() + U8::from(1);
+            let proposal_id = self.sessions.getter(session_id).proposal_count.get() + U16::from(1);
 
             self.sessions
                 .setter(session_id)
                 .proposals
                 .setter(proposal_id)
                 .title
-                .set(to_b256(&title));
+                .set_str(&title);
             self.sessions
                 .setter(session_id)
                 .proposals
                 .setter(proposal_id)
                 .description
-                .set(to_b256(&description));
+                .set_str(&description);
             self.sessions
                 .setter(session_id)
                 .proposals
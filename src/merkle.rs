@@ -0,0 +1,92 @@
+//!
+//! Merkle trees over (address, credit_allocation) leaves for per-session voter eligibility.
+//!
+//! Leaves are `keccak256(abi.encodePacked(address, uint256))` and parents hash the sorted
+//! pair of children, matching OpenZeppelin's `MerkleProof` so roots can also be produced
+//! off-chain with standard tooling.
+//!
+
+use alloc::vec::Vec;
+use stylus_sdk::{
+    alloy_primitives::{Address, B256, U256},
+    crypto::keccak,
+};
+
+/// Hash of a single eligibility leaf
+pub fn leaf_hash(account: Address, allocation: U256) -> B256 {
+    let mut packed = [0u8; 52];
+    packed[..20].copy_from_slice(account.as_slice());
+    packed[20..].copy_from_slice(&allocation.to_be_bytes::<32>());
+    keccak(packed)
+}
+
+/// Parent of two nodes, hashed in sorted order so proofs need no left/right flags
+pub fn hash_pair(a: B256, b: B256) -> B256 {
+    let (first, second) = if a <= b { (a, b) } else { (b, a) };
+    let mut packed = [0u8; 64];
+    packed[..32].copy_from_slice(first.as_slice());
+    packed[32..].copy_from_slice(second.as_slice());
+    keccak(packed)
+}
+
+/// Check that `leaf` is part of the tree committed to by `root`
+pub fn verify(proof: &[B256], root: B256, leaf: B256) -> bool {
+    proof.iter().fold(leaf, |node, &sibling| hash_pair(node, sibling)) == root
+}
+
+/// Off-chain helper to build eligibility trees and proofs, e.g. in tests or deploy scripts
+pub struct MerkleTree {
+    layers: Vec<Vec<B256>>,
+}
+
+impl MerkleTree {
+    /// Build a tree from (account, allocation) entries, keeping their order
+    pub fn new(entries: &[(Address, U256)]) -> Self {
+        let mut layers = Vec::new();
+        let mut layer: Vec<B256> = entries
+            .iter()
+            .map(|&(account, allocation)| leaf_hash(account, allocation))
+            .collect();
+
+        while layer.len() > 1 {
+            // An unpaired node is carried up unchanged
+            let next = layer
+                .chunks(2)
+                .map(|pair| match pair {
+                    [a, b] => hash_pair(*a, *b),
+                    [a] => *a,
+                    _ => unreachable!(),
+                })
+                .collect();
+            layers.push(layer);
+            layer = next;
+        }
+        layers.push(layer);
+
+        Self { layers }
+    }
+
+    /// Root to pass to `create_session`; zero for an empty tree
+    pub fn root(&self) -> B256 {
+        self.layers
+            .last()
+            .and_then(|top| top.first().copied())
+            .unwrap_or(B256::ZERO)
+    }
+
+    /// Proof for the entry at `index`, or `None` if out of range
+    pub fn proof(&self, mut index: usize) -> Option<Vec<B256>> {
+        if index >= self.layers[0].len() {
+            return None;
+        }
+
+        let mut proof = Vec::new();
+        for layer in &self.layers[..self.layers.len() - 1] {
+            if let Some(&sibling) = layer.get(index ^ 1) {
+                proof.push(sibling);
+            }
+            index /= 2;
+        }
+        Some(proof)
+    }
+}
@@ -0,0 +1,473 @@
+//!
+//! Quadratic funding rounds, built with the `quadratic-funding` feature.
+//!
+//! Eligible voters contribute ETH or an ERC-20 token to proposals while a session is open, and a
+//! matching pool is shared between them with the CLR formula in [`crate::funding`] once it ends.
+//!
+
+use alloc::vec::Vec;
+use stylus_sdk::{
+    alloy_primitives::{Address, U16, U256, U64},
+    alloy_sol_types::SolCall,
+    prelude::*,
+};
+
+use crate::{
+    funding, ContributionMade, FundsClaimed, FundsRefunded, InvalidAmount, InvalidProposal,
+    InvalidProposalCount, InvalidSession, MatchingDustSwept, MatchingFinalized,
+    MatchingPoolFunded, QuadraticVoting, QuadraticVotingError, TransferFailed, Unauthorized,
+    ADMIN_ROLE, IERC20,
+};
+
+/// Entrypoints of quadratic funding rounds
+pub trait QuadraticFunding {
+    /// @notice Turn a session into a quadratic funding round
+    /// @dev Only the session creator or an ADMIN_ROLE holder, and only before start_time. Eligible
+    ///      voters contribute to proposals during the session window, and a matching pool is
+    ///      shared between proposals with the CLR formula once the session ends
+    /// @param session_id The session to configure
+    /// @param token ERC-20 used for contributions and the pool, or Address::ZERO for ETH
+    /// @param recipients Payout address for each proposal, in proposal ID order
+    /// @return Result indicating success or specific error
+    fn enable_quadratic_funding(
+        &mut self,
+        session_id: U64,
+        token: Address,
+        recipients: Vec<Address>,
+    ) -> Result<(), QuadraticVotingError>;
+
+    /// @notice Add funds to a quadratic funding round's matching pool
+    /// @dev Open to anyone until matching is finalized. In ETH rounds msg.value must equal amount;
+    ///      in token rounds the amount is pulled with transferFrom
+    /// @param session_id The funding round
+    /// @param amount Amount to add, in wei or token base units
+    /// @return Result indicating success or specific error
+    fn fund_matching_pool(&mut self, session_id: U64, amount: U256) -> Result<(), QuadraticVotingError>;
+
+    /// @notice Contribute to a proposal in a quadratic funding round
+    /// @dev Only eligible voters, while the session is open for voting. Repeat contributions add
+    ///      up per contributor, so splitting a contribution does not raise the match
+    /// @param session_id The funding round
+    /// @param proposal_id The proposal to fund
+    /// @param amount Amount to contribute, in wei or token base units
+    /// @return Result indicating success or specific error
+    fn contribute(
+        &mut self,
+        session_id: U64,
+        proposal_id: U16,
+        amount: U256,
+    ) -> Result<(), QuadraticVotingError>;
+
+    /// @notice Share the matching pool between proposals once a funding round has ended
+    /// @dev Callable by anyone after end_time. Each proposal's ideal match is (Σ√cᵢ)² − Σcᵢ and
+    ///      the pool is split in proportion to it, rounding down. The session manager can collect
+    ///      the rounding dust with sweep_matching_dust. If no proposal earns a match, pool funders
+    ///      can take their funds back with refund
+    /// @param session_id The funding round
+    /// @return The total amount matched
+    fn finalize_matching(&mut self, session_id: U64) -> Result<U256, QuadraticVotingError>;
+
+    /// @notice Pay out a proposal's contributions plus its matching share to its recipient
+    /// @dev Only the proposal's recipient, once, after finalize_matching
+    /// @param session_id The funding round
+    /// @param proposal_id The proposal to claim for
+    /// @return The amount paid out
+    fn claim(&mut self, session_id: U64, proposal_id: U16) -> Result<U256, QuadraticVotingError>;
+
+    /// @notice Take back funds from a funding round that will not pay out
+    /// @dev In a cancelled round, contributors and pool funders recover everything they sent.
+    ///      After a finalization that matched nothing, pool funders recover their pool funding.
+    ///      Once any proposal is matched the pool is spent, all but the rounding dust, so there is
+    ///      nothing to refund; partial pool refunds are not supported
+    /// @param session_id The funding round
+    /// @return The amount refunded
+    fn refund(&mut self, session_id: U64) -> Result<U256, QuadraticVotingError>;
+
+    /// @notice Send a funding round's rounding dust to the caller
+    /// @dev Only the session creator or an ADMIN_ROLE holder, once, after finalize_matching has
+    ///      matched something. Proportional matching rounds down, leaving matching_pool minus
+    ///      total_matched unclaimable; a round that matched nothing keeps its pool for refund
+    /// @param session_id The funding round
+    /// @return The amount swept
+    fn sweep_matching_dust(&mut self, session_id: U64) -> Result<U256, QuadraticVotingError>;
+
+    /// @notice Get the state of a quadratic funding round
+    /// @param session_id The funding round
+    /// @return Tuple containing (enabled, token, matching_pool, finalized, total_matched); token is zero for ETH
+    fn get_matching_pool(
+        &self,
+        session_id: U64,
+    ) -> Result<(bool, Address, U256, bool, U256), QuadraticVotingError>;
+
+    /// @notice Get a proposal's funding in a quadratic funding round
+    /// @param session_id The funding round
+    /// @param proposal_id The proposal to query
+    /// @return Tuple containing (recipient, contributions, match_amount, claimed); match_amount is zero until finalized
+    fn get_proposal_funding(
+        &self,
+        session_id: U64,
+        proposal_id: U16,
+    ) -> Result<(Address, U256, U256, bool), QuadraticVotingError>;
+}
+
+#[public]
+impl QuadraticFunding for QuadraticVoting {
+    fn enable_quadratic_funding(
+        &mut self,
+        session_id: U64,
+        token: Address,
+        recipients: Vec<Address>,
+    ) -> Result<(), QuadraticVotingError> {
+        self.only_session_manager(session_id, ADMIN_ROLE)?;
+
+        let now = U256::from(self.vm().block_timestamp());
+        let session = self.sessions.get(session_id);
+        if now >= session.start_time.get() {
+            return Err(QuadraticVotingError::InvalidSession(InvalidSession {}));
+        }
+        if recipients.len() != session.proposal_count.get().to::<usize>() {
+            return Err(QuadraticVotingError::InvalidProposalCount(InvalidProposalCount {}));
+        }
+        if recipients.contains(&Address::ZERO) {
+            return Err(QuadraticVotingError::InvalidProposal(InvalidProposal {}));
+        }
+
+        let mut session = self.sessions.setter(session_id);
+        session.quadratic_funding.set(true);
+        session.funding_token.set(token);
+        for (index, recipient) in recipients.into_iter().enumerate() {
+            session
+                .proposals
+                .setter(U16::from(index + 1))
+                .recipient
+                .set(recipient);
+        }
+        Ok(())
+    }
+
+    #[payable]
+    fn fund_matching_pool(&mut self, session_id: U64, amount: U256) -> Result<(), QuadraticVotingError> {
+        let caller = self.vm().msg_sender();
+        let session = self.sessions.get(session_id);
+        if !session.quadratic_funding.get() || session.cancelled.get() || session.matching_finalized.get() {
+            return Err(QuadraticVotingError::InvalidSession(InvalidSession {}));
+        }
+        let pool = session
+            .matching_pool
+            .get()
+            .checked_add(amount)
+            .ok_or(QuadraticVotingError::InvalidAmount(InvalidAmount {}))?;
+        let funded = session.pool_funded.get(caller) + amount;
+
+        self.collect_payment(session_id, caller, amount)?;
+        let mut session = self.sessions.setter(session_id);
+        session.matching_pool.set(pool);
+        session.pool_funded.setter(caller).set(funded);
+
+        log(
+            self.vm(),
+            MatchingPoolFunded {
+                session_id: session_id.as_limbs()[0],
+                funder: caller,
+                amount,
+            },
+        );
+
+        Ok(())
+    }
+
+    #[payable]
+    fn contribute(
+        &mut self,
+        session_id: U64,
+        proposal_id: U16,
+        amount: U256,
+    ) -> Result<(), QuadraticVotingError> {
+        let caller = self.vm().msg_sender();
+        self.check_voter(session_id, caller)?;
+        self.check_voting_open(session_id)?;
+
+        let session = self.sessions.get(session_id);
+        if !session.quadratic_funding.get() {
+            return Err(QuadraticVotingError::InvalidSession(InvalidSession {}));
+        }
+        if proposal_id == U16::ZERO
+            || proposal_id > session.proposal_count.get()
+            || session.proposals.get(proposal_id).rejected.get()
+        {
+            return Err(QuadraticVotingError::InvalidProposal(InvalidProposal {}));
+        }
+
+        let proposal = session.proposals.get(proposal_id);
+        let previous = proposal.contributed.get(caller);
+        let contributed = previous
+            .checked_add(amount)
+            .ok_or(QuadraticVotingError::InvalidAmount(InvalidAmount {}))?;
+        let contributions = proposal
+            .contributions
+            .get()
+            .checked_add(amount)
+            .ok_or(QuadraticVotingError::InvalidAmount(InvalidAmount {}))?;
+        // isqrt is monotonic, so the new root is never below the previous one
+        let sqrt_sum =
+            proposal.sqrt_sum.get() + funding::isqrt(contributed) - funding::isqrt(previous);
+        let contributed_total = session
+            .contributed_total
+            .get(caller)
+            .checked_add(amount)
+            .ok_or(QuadraticVotingError::InvalidAmount(InvalidAmount {}))?;
+
+        self.collect_payment(session_id, caller, amount)?;
+        let mut session = self.sessions.setter(session_id);
+        session.contributed_total.setter(caller).set(contributed_total);
+        let mut proposal = session.proposals.setter(proposal_id);
+        proposal.contributed.setter(caller).set(contributed);
+        proposal.contributions.set(contributions);
+        proposal.sqrt_sum.set(sqrt_sum);
+
+        log(
+            self.vm(),
+            ContributionMade {
+                session_id: session_id.as_limbs()[0],
+                proposal_id: proposal_id.to::<u16>(),
+                contributor: caller,
+                amount,
+            },
+        );
+
+        Ok(())
+    }
+
+    fn finalize_matching(&mut self, session_id: U64) -> Result<U256, QuadraticVotingError> {
+        let now = U256::from(self.vm().block_timestamp());
+        let session = self.sessions.get(session_id);
+        if !session.quadratic_funding.get()
+            || session.cancelled.get()
+            || session.matching_finalized.get()
+            || now < session.end_time.get()
+        {
+            return Err(QuadraticVotingError::InvalidSession(InvalidSession {}));
+        }
+
+        let proposal_count = session.proposal_count.get().to::<u16>();
+        let mut ideals = Vec::with_capacity(proposal_count as usize);
+        let mut total_ideal = U256::ZERO;
+        for proposal_id in 1..=proposal_count {
+            let proposal = session.proposals.get(U16::from(proposal_id));
+            let ideal = funding::ideal_match(proposal.sqrt_sum.get(), proposal.contributions.get())
+                .ok_or(QuadraticVotingError::InvalidAmount(InvalidAmount {}))?;
+            total_ideal = total_ideal
+                .checked_add(ideal)
+                .ok_or(QuadraticVotingError::InvalidAmount(InvalidAmount {}))?;
+            ideals.push(ideal);
+        }
+
+        let pool = session.matching_pool.get();
+        let mut total_matched = U256::ZERO;
+        let mut session = self.sessions.setter(session_id);
+        for (index, ideal) in ideals.into_iter().enumerate() {
+            let matched = funding::scaled_match(pool, ideal, total_ideal);
+            session
+                .proposals
+                .setter(U16::from(index + 1))
+                .match_amount
+                .set(matched);
+            total_matched += matched;
+        }
+        session.matching_finalized.set(true);
+        session.total_matched.set(total_matched);
+
+        log(
+            self.vm(),
+            MatchingFinalized {
+                session_id: session_id.as_limbs()[0],
+                total_matched,
+            },
+        );
+
+        Ok(total_matched)
+    }
+
+    fn claim(&mut self, session_id: U64, proposal_id: U16) -> Result<U256, QuadraticVotingError> {
+        let caller = self.vm().msg_sender();
+        let session = self.sessions.get(session_id);
+        if !session.matching_finalized.get() {
+            return Err(QuadraticVotingError::InvalidSession(InvalidSession {}));
+        }
+        if proposal_id == U16::ZERO || proposal_id > session.proposal_count.get() {
+            return Err(QuadraticVotingError::InvalidProposal(InvalidProposal {}));
+        }
+        let proposal = session.proposals.get(proposal_id);
+        if proposal.recipient.get() != caller {
+            return Err(QuadraticVotingError::Unauthorized(Unauthorized {}));
+        }
+        if proposal.claimed.get() {
+            return Err(QuadraticVotingError::InvalidAmount(InvalidAmount {}));
+        }
+        let amount = proposal.contributions.get() + proposal.match_amount.get();
+
+        // Mark the claim before paying out so a re-entrant recipient cannot claim twice
+        self.sessions
+            .setter(session_id)
+            .proposals
+            .setter(proposal_id)
+            .claimed
+            .set(true);
+        self.pay_out(session_id, caller, amount)?;
+
+        log(
+            self.vm(),
+            FundsClaimed {
+                session_id: session_id.as_limbs()[0],
+                proposal_id: proposal_id.to::<u16>(),
+                recipient: caller,
+                amount,
+            },
+        );
+
+        Ok(amount)
+    }
+
+    fn refund(&mut self, session_id: U64) -> Result<U256, QuadraticVotingError> {
+        let caller = self.vm().msg_sender();
+        let session = self.sessions.get(session_id);
+        if !session.quadratic_funding.get() {
+            return Err(QuadraticVotingError::InvalidSession(InvalidSession {}));
+        }
+        let amount = if session.cancelled.get() {
+            session.pool_funded.get(caller) + session.contributed_total.get(caller)
+        } else if session.matching_finalized.get() && session.total_matched.get().is_zero() {
+            session.pool_funded.get(caller)
+        } else {
+            return Err(QuadraticVotingError::InvalidSession(InvalidSession {}));
+        };
+        if amount.is_zero() {
+            return Err(QuadraticVotingError::InvalidAmount(InvalidAmount {}));
+        }
+
+        let mut session = self.sessions.setter(session_id);
+        session.pool_funded.setter(caller).set(U256::ZERO);
+        session.contributed_total.setter(caller).set(U256::ZERO);
+        self.pay_out(session_id, caller, amount)?;
+
+        log(
+            self.vm(),
+            FundsRefunded {
+                session_id: session_id.as_limbs()[0],
+                account: caller,
+                amount,
+            },
+        );
+
+        Ok(amount)
+    }
+
+    fn sweep_matching_dust(&mut self, session_id: U64) -> Result<U256, QuadraticVotingError> {
+        self.only_session_manager(session_id, ADMIN_ROLE)?;
+
+        let session = self.sessions.get(session_id);
+        if !session.matching_finalized.get() || session.total_matched.get().is_zero() {
+            return Err(QuadraticVotingError::InvalidSession(InvalidSession {}));
+        }
+        let amount = session.matching_pool.get() - session.total_matched.get();
+        if session.dust_swept.get() || amount.is_zero() {
+            return Err(QuadraticVotingError::InvalidAmount(InvalidAmount {}));
+        }
+
+        let caller = self.vm().msg_sender();
+        self.sessions.setter(session_id).dust_swept.set(true);
+        self.pay_out(session_id, caller, amount)?;
+
+        log(
+            self.vm(),
+            MatchingDustSwept {
+                session_id: session_id.as_limbs()[0],
+                to: caller,
+                amount,
+            },
+        );
+
+        Ok(amount)
+    }
+
+    fn get_matching_pool(
+        &self,
+        session_id: U64,
+    ) -> Result<(bool, Address, U256, bool, U256), QuadraticVotingError> {
+        let session = self.sessions.get(session_id);
+        if session.creator.get() == Address::ZERO {
+            return Err(QuadraticVotingError::InvalidSession(InvalidSession {}));
+        }
+
+        Ok((
+            session.quadratic_funding.get(),
+            session.funding_token.get(),
+            session.matching_pool.get(),
+            session.matching_finalized.get(),
+            session.total_matched.get(),
+        ))
+    }
+
+    fn get_proposal_funding(
+        &self,
+        session_id: U64,
+        proposal_id: U16,
+    ) -> Result<(Address, U256, U256, bool), QuadraticVotingError> {
+        let session = self.sessions.get(session_id);
+        if proposal_id == U16::ZERO || proposal_id > session.proposal_count.get() {
+            return Err(QuadraticVotingError::InvalidProposal(InvalidProposal {}));
+        }
+
+        let proposal = session.proposals.get(proposal_id);
+        Ok((
+            proposal.recipient.get(),
+            proposal.contributions.get(),
+            proposal.match_amount.get(),
+            proposal.claimed.get(),
+        ))
+    }
+}
+
+impl QuadraticVoting {
+    /// @dev Takes `amount` from `from` in a funding round's currency: msg.value for ETH rounds,
+    ///      transferFrom for token rounds (where no ETH may be sent)
+    fn collect_payment(
+        &mut self,
+        session_id: U64,
+        from: Address,
+        amount: U256,
+    ) -> Result<(), QuadraticVotingError> {
+        let token = self.sessions.get(session_id).funding_token.get();
+        let value = self.vm().msg_value();
+        let value_ok = if token == Address::ZERO { value == amount } else { value.is_zero() };
+        if amount.is_zero() || !value_ok {
+            return Err(QuadraticVotingError::InvalidAmount(InvalidAmount {}));
+        }
+        if token == Address::ZERO {
+            return Ok(());
+        }
+
+        let contract = self.vm().contract_address();
+        self.call_token(
+            token,
+            IERC20::transferFromCall {
+                from,
+                to: contract,
+                amount,
+            }
+            .abi_encode(),
+        )
+    }
+
+    /// @dev Sends `amount` of a funding round's currency to `to`
+    fn pay_out(&mut self, session_id: U64, to: Address, amount: U256) -> Result<(), QuadraticVotingError> {
+        let token = self.sessions.get(session_id).funding_token.get();
+        if token == Address::ZERO {
+            return self
+                .vm()
+                .transfer_eth(to, amount)
+                .map_err(|_| QuadraticVotingError::TransferFailed(TransferFailed {}));
+        }
+        self.call_token(token, IERC20::transferCall { to, amount }.abi_encode())
+    }
+}
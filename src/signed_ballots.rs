@@ -0,0 +1,165 @@
+//!
+//! EIP-712 signed ballots, built with the `signed-ballots` feature.
+//!
+//! Voters sign Ballot messages off-chain (see [`crate::eip712`]) and a relayer submits them,
+//! paying the gas. Each voter has a nonce that every accepted ballot consumes.
+//!
+
+use alloc::vec::Vec;
+use stylus_sdk::{
+    abi::Bytes,
+    alloy_primitives::{Address, FixedBytes, U16, U256, U64},
+    prelude::*,
+};
+
+use crate::{
+    eip712, BallotRejected, InvalidNonce, InvalidSignature, QuadraticVoting,
+    QuadraticVotingError, SignatureExpired, SignedBallot,
+};
+
+/// Entrypoints for relaying signed ballots
+pub trait SignedBallots {
+    /// @notice Cast votes on behalf of a voter who signed an EIP-712 Ballot off-chain
+    /// @dev Lets a relayer pay gas for the voter. The ballot goes through the same checks as
+    ///      vote; the voter's nonce is consumed only when the votes are accepted
+    /// @param session_id The session to vote in
+    /// @param proposal_ids Array of proposal IDs to vote for (each existing and listed at most once)
+    /// @param vote_counts Corresponding vote intensities (cost = intensity²)
+    /// @param against Whether each vote opposes its proposal, as in vote_with_intensities; empty to support all
+    /// @param voter The account that signed the ballot
+    /// @param nonce The voter's current nonce, see get_nonce
+    /// @param deadline Last block timestamp at which the signature is valid
+    /// @param signature 65-byte r || s || v signature over the ballot digest
+    /// @return Result indicating success or specific error
+    #[allow(clippy::too_many_arguments)]
+    fn vote_by_sig(
+        &mut self,
+        session_id: U64,
+        proposal_ids: Vec<U16>,
+        vote_counts: Vec<U64>,
+        against: Vec<bool>,
+        voter: Address,
+        nonce: U256,
+        deadline: U256,
+        signature: Bytes,
+    ) -> Result<(), QuadraticVotingError>;
+
+    /// @notice Relay many signed ballots in one transaction
+    /// @dev Each ballot is verified and applied exactly like vote_by_sig. A ballot that fails is
+    ///      skipped and reported with BallotRejected (the 4-byte selector of its error) instead of
+    ///      reverting the batch. Ballots are applied in order, so one voter may include several
+    ///      ballots with consecutive nonces
+    /// @param ballots (session_id, proposal_ids, vote_counts, against, voter, nonce, deadline, signature) tuples
+    /// @return Whether each ballot was accepted, in input order
+    fn submit_ballots(&mut self, ballots: Vec<SignedBallot>) -> Vec<bool>;
+
+    /// @notice Get the nonce the next signed ballot of a voter must use
+    /// @param voter The voter address
+    /// @return The current nonce
+    fn get_nonce(&self, voter: Address) -> U256;
+}
+
+#[public]
+impl SignedBallots for QuadraticVoting {
+    fn vote_by_sig(
+        &mut self,
+        session_id: U64,
+        proposal_ids: Vec<U16>,
+        vote_counts: Vec<U64>,
+        against: Vec<bool>,
+        voter: Address,
+        nonce: U256,
+        deadline: U256,
+        signature: Bytes,
+    ) -> Result<(), QuadraticVotingError> {
+        self.apply_signed_ballot(
+            session_id,
+            proposal_ids,
+            vote_counts,
+            against,
+            voter,
+            nonce,
+            deadline,
+            &signature,
+        )
+    }
+
+    fn submit_ballots(&mut self, ballots: Vec<SignedBallot>) -> Vec<bool> {
+        let mut statuses = Vec::with_capacity(ballots.len());
+        for (session_id, proposal_ids, vote_counts, against, voter, nonce, deadline, signature) in ballots {
+            let result = self.apply_signed_ballot(
+                session_id,
+                proposal_ids,
+                vote_counts,
+                against,
+                voter,
+                nonce,
+                deadline,
+                &signature,
+            );
+            match result {
+                Ok(()) => statuses.push(true),
+                Err(err) => {
+                    let encoded: Vec<u8> = err.into();
+                    log(
+                        self.vm(),
+                        BallotRejected {
+                            voter,
+                            reason: FixedBytes::from_slice(&encoded[..4]),
+                        },
+                    );
+                    statuses.push(false);
+                }
+            }
+        }
+        statuses
+    }
+
+    fn get_nonce(&self, voter: Address) -> U256 {
+        self.nonces.get(voter)
+    }
+}
+
+impl QuadraticVoting {
+    /// @dev Verifies a signed ballot and casts its votes for the signer. Every check runs
+    ///      before any state is written, so a rejected ballot leaves no trace
+    #[allow(clippy::too_many_arguments)]
+    fn apply_signed_ballot(
+        &mut self,
+        session_id: U64,
+        proposal_ids: Vec<U16>,
+        vote_counts: Vec<U64>,
+        against: Vec<bool>,
+        voter: Address,
+        nonce: U256,
+        deadline: U256,
+        signature: &[u8],
+    ) -> Result<(), QuadraticVotingError> {
+        if U256::from(self.vm().block_timestamp()) > deadline {
+            return Err(QuadraticVotingError::SignatureExpired(SignatureExpired {}));
+        }
+        if nonce != self.nonces.get(voter) {
+            return Err(QuadraticVotingError::InvalidNonce(InvalidNonce {}));
+        }
+
+        let digest = eip712::ballot_digest(
+            self.vm().chain_id(),
+            self.vm().contract_address(),
+            session_id,
+            &proposal_ids,
+            &vote_counts,
+            &against,
+            voter,
+            nonce,
+            deadline,
+        );
+        if self.recover_signer(digest, signature)? != voter {
+            return Err(QuadraticVotingError::InvalidSignature(InvalidSignature {}));
+        }
+
+        self.check_not_delegated(session_id, voter)?;
+        self.cast_votes(session_id, voter, proposal_ids, vote_counts, against)?;
+        self.nonces.setter(voter).set(nonce + U256::from(1));
+        Ok(())
+    }
+}
@@ -0,0 +1,169 @@
+//!
+//! ERC-20 token-backed credits, built with the `token-credits` feature.
+//!
+//! A token-backed session replaces credits_per_voter with the amount of an ERC-20 token each
+//! voter locks in the contract, one credit per base unit.
+//!
+
+use alloc::vec::Vec;
+use stylus_sdk::{
+    alloy_primitives::{Address, B256, U256, U64},
+    alloy_sol_types::SolCall,
+    prelude::*,
+};
+
+use crate::{
+    CreditsDeposited, CreditsWithdrawn, InsufficientCredits, InvalidSession, QuadraticVoting,
+    QuadraticVotingError, ADMIN_ROLE, IERC20,
+};
+
+/// Entrypoints of token-backed sessions
+pub trait TokenCredits {
+    /// @notice Back voter budgets with an ERC-20 token instead of credits_per_voter
+    /// @dev Only the session creator or an ADMIN_ROLE holder, and only before start_time. Each
+    ///      registered voter's budget becomes the amount of `token` they deposit with
+    ///      deposit_credits, one credit per base unit. Not available in Merkle-gated sessions
+    /// @param session_id The session to configure
+    /// @param token The ERC-20 token voters lock for the session
+    /// @return Result indicating success or specific error
+    fn set_credit_token(&mut self, session_id: U64, token: Address) -> Result<(), QuadraticVotingError>;
+
+    /// @notice Lock tokens in a token-backed session to raise the caller's credit budget
+    /// @dev Pulls `amount` with transferFrom, so the caller must approve this contract first.
+    ///      Deposits are accepted until end_time and stay locked until withdraw_credits is allowed
+    /// @param session_id The session to deposit into
+    /// @param amount Token amount in base units
+    /// @return Result indicating success or specific error
+    fn deposit_credits(&mut self, session_id: U64, amount: U256) -> Result<(), QuadraticVotingError>;
+
+    /// @notice Return the caller's locked tokens once a token-backed session is over
+    /// @dev Allowed after end_time (after the reveal phase in commit-reveal sessions) or as soon
+    ///      as the session is cancelled. Votes already cast stay counted
+    /// @param session_id The session to withdraw from
+    /// @return The amount returned
+    fn withdraw_credits(&mut self, session_id: U64) -> Result<U256, QuadraticVotingError>;
+
+    /// @notice Get the token backing a session's credits and a voter's locked deposit
+    /// @param session_id The session to query
+    /// @param voter The voter address
+    /// @return Tuple containing (token, deposit) where token is zero for sessions using credits_per_voter
+    fn get_credit_deposit(
+        &self,
+        session_id: U64,
+        voter: Address,
+    ) -> Result<(Address, U256), QuadraticVotingError>;
+}
+
+#[public]
+impl TokenCredits for QuadraticVoting {
+    fn set_credit_token(&mut self, session_id: U64, token: Address) -> Result<(), QuadraticVotingError> {
+        self.only_session_manager(session_id, ADMIN_ROLE)?;
+
+        let now = U256::from(self.vm().block_timestamp());
+        let session = self.sessions.get(session_id);
+        if token == Address::ZERO
+            || now >= session.start_time.get()
+            || session.eligibility_root.get() != B256::ZERO
+        {
+            return Err(QuadraticVotingError::InvalidSession(InvalidSession {}));
+        }
+
+        self.sessions.setter(session_id).credit_token.set(token);
+        Ok(())
+    }
+
+    fn deposit_credits(&mut self, session_id: U64, amount: U256) -> Result<(), QuadraticVotingError> {
+        let caller = self.vm().msg_sender();
+        self.check_voter(session_id, caller)?;
+
+        let now = U256::from(self.vm().block_timestamp());
+        let session = self.sessions.get(session_id);
+        let token = session.credit_token.get();
+        if token == Address::ZERO || !session.active.get() || now >= session.end_time.get() {
+            return Err(QuadraticVotingError::InvalidSession(InvalidSession {}));
+        }
+        if amount == U256::ZERO {
+            return Err(QuadraticVotingError::InsufficientCredits(InsufficientCredits {}));
+        }
+        let balance = session
+            .deposits
+            .get(caller)
+            .checked_add(amount)
+            .ok_or(QuadraticVotingError::InsufficientCredits(InsufficientCredits {}))?;
+
+        let contract = self.vm().contract_address();
+        self.call_token(
+            token,
+            IERC20::transferFromCall {
+                from: caller,
+                to: contract,
+                amount,
+            }
+            .abi_encode(),
+        )?;
+        self.sessions
+            .setter(session_id)
+            .deposits
+            .setter(caller)
+            .set(balance);
+
+        log(
+            self.vm(),
+            CreditsDeposited {
+                session_id: session_id.as_limbs()[0],
+                voter: caller,
+                amount,
+            },
+        );
+
+        Ok(())
+    }
+
+    fn withdraw_credits(&mut self, session_id: U64) -> Result<U256, QuadraticVotingError> {
+        let caller = self.vm().msg_sender();
+        let now = U256::from(self.vm().block_timestamp());
+        let session = self.sessions.get(session_id);
+        let token = session.credit_token.get();
+        let unlock_time = session.end_time.get() + U256::from(session.reveal_duration.get());
+        if token == Address::ZERO || (!session.cancelled.get() && now < unlock_time) {
+            return Err(QuadraticVotingError::InvalidSession(InvalidSession {}));
+        }
+
+        let amount = session.deposits.get(caller);
+        if amount == U256::ZERO {
+            return Err(QuadraticVotingError::InsufficientCredits(InsufficientCredits {}));
+        }
+
+        // Clear the deposit before the external call so a re-entrant token cannot withdraw twice
+        self.sessions
+            .setter(session_id)
+            .deposits
+            .setter(caller)
+            .set(U256::ZERO);
+        self.call_token(token, IERC20::transferCall { to: caller, amount }.abi_encode())?;
+
+        log(
+            self.vm(),
+            CreditsWithdrawn {
+                session_id: session_id.as_limbs()[0],
+                voter: caller,
+                amount,
+            },
+        );
+
+        Ok(amount)
+    }
+
+    fn get_credit_deposit(
+        &self,
+        session_id: U64,
+        voter: Address,
+    ) -> Result<(Address, U256), QuadraticVotingError> {
+        let session = self.sessions.get(session_id);
+        if session.creator.get() == Address::ZERO {
+            return Err(QuadraticVotingError::InvalidSession(InvalidSession {}));
+        }
+
+        Ok((session.credit_token.get(), session.deposits.get(voter)))
+    }
+}
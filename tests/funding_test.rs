@@ -0,0 +1,31 @@
+use stylus_quadratic_voting::funding::{ideal_match, isqrt, scaled_match};
+use stylus_sdk::alloy_primitives::U256;
+
+#[test]
+fn test_isqrt_rounds_down() {
+    for n in 0u64..2000 {
+        let root = isqrt(U256::from(n)).to::<u64>();
+        assert!(root * root <= n && (root + 1) * (root + 1) > n, "isqrt({n}) = {root}");
+    }
+
+    let max_root = (U256::from(1) << 128) - U256::from(1);
+    assert_eq!(isqrt(U256::MAX), max_root);
+    assert_eq!(isqrt(max_root * max_root), max_root);
+    assert_eq!(isqrt(max_root * max_root - U256::from(1)), max_root - U256::from(1));
+}
+
+#[test]
+fn test_clr_match() {
+    // Two contributions of 4: (2 + 2)² − 8
+    assert_eq!(ideal_match(U256::from(4), U256::from(8)), Some(U256::from(8)));
+    // A single contributor earns nothing, even when its root was rounded down
+    assert_eq!(ideal_match(U256::from(4), U256::from(16)), Some(U256::ZERO));
+    assert_eq!(ideal_match(U256::from(4), U256::from(17)), Some(U256::ZERO));
+    assert_eq!(ideal_match(U256::MAX, U256::ZERO), None);
+
+    assert_eq!(scaled_match(U256::from(700), U256::from(8), U256::from(14)), U256::from(400));
+    assert_eq!(scaled_match(U256::from(10), U256::from(1), U256::from(3)), U256::from(3));
+    assert_eq!(scaled_match(U256::from(10), U256::ZERO, U256::ZERO), U256::ZERO);
+    // Scaling does not overflow for pools near the top of the range
+    assert_eq!(scaled_match(U256::MAX, U256::MAX, U256::MAX), U256::MAX);
+}
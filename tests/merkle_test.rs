@@ -0,0 +1,44 @@
+use stylus_quadratic_voting::merkle::{hash_pair, leaf_hash, verify, MerkleTree};
+use stylus_sdk::alloy_primitives::{Address, B256, U256};
+
+fn entries(count: u8) -> Vec<(Address, U256)> {
+    (1..=count)
+        .map(|i| (Address::from([i; 20]), U256::from(i as u64 * 10)))
+        .collect()
+}
+
+#[test]
+fn test_every_proof_verifies() {
+    for count in 1..=9u8 {
+        let entries = entries(count);
+        let tree = MerkleTree::new(&entries);
+        let root = tree.root();
+
+        for (i, &(account, allocation)) in entries.iter().enumerate() {
+            let proof = tree.proof(i).unwrap();
+            assert!(verify(&proof, root, leaf_hash(account, allocation)));
+
+            // The allocation is bound into the leaf
+            assert!(!verify(&proof, root, leaf_hash(account, allocation + U256::from(1))));
+        }
+        assert!(tree.proof(entries.len()).is_none());
+    }
+}
+
+#[test]
+fn test_tree_shape() {
+    let entries = entries(3);
+    let tree = MerkleTree::new(&entries);
+    let leaves: Vec<B256> = entries.iter().map(|&(a, c)| leaf_hash(a, c)).collect();
+
+    // The unpaired third leaf is carried up to the root level
+    assert_eq!(
+        tree.root(),
+        hash_pair(hash_pair(leaves[0], leaves[1]), leaves[2])
+    );
+    assert_eq!(tree.proof(2).unwrap(), vec![hash_pair(leaves[0], leaves[1])]);
+
+    // Single leaf trees use the leaf as root; empty trees have no root
+    assert_eq!(MerkleTree::new(&entries[..1]).root(), leaves[0]);
+    assert_eq!(MerkleTree::new(&[]).root(), B256::ZERO);
+}
@@ -1,10 +1,27 @@
 use stylus_quadratic_voting::{
     QuadraticVoting, QuadraticVotingError, InvalidSession,
-    VoterNotRegistered, InsufficientCredits
+    VoterNotRegistered, InsufficientCredits, InvalidProposal,
+    SessionClosed, SessionCancelled, SessionExtended, SessionPaused,
+    RoleGranted, RoleRevoked, ADMIN_ROLE, SESSION_CREATOR_ROLE, VOTER_REGISTRAR_ROLE,
+    PAUSER_ROLE, VoterRemoved, VoterSuspended, REGISTRATION_OPEN, REGISTRATION_APPROVAL,
+    REGISTRATION_ALLOWLIST, merkle::MerkleTree, vote_commitment, VoteCommitted, VoteRevealed,
+    DelegateChanged, eip712::{ballot_digest, signer}, BallotRejected, InvalidSignature,
+    IERC20, CreditsDeposited, CreditsWithdrawn, MatchingFinalized, FundsClaimed, MatchingDustSwept,
+    ProposalSubmitted, ProposalRejected, ForfeitedBondsWithdrawn, SessionCreated, identity_commitment,
+    eip712::registration_digest, VoterRegistered, TIE_BREAK_LOWEST_ID, TIE_BREAK_EARLIEST, TIE_BREAK_CREATOR,
+    TIE_BREAK_NONE, TieResolved, OUTCOME_PASSED, OUTCOME_FAILED_QUORUM, OUTCOME_NO_WINNER,
+    OUTCOME_TIED, SessionFinalized, WinnersSelected, BudgetAllocated, QuadraticFunding, SignedBallots,
+    TokenCredits
 };
-use stylus_sdk::alloy_primitives::{Address, U64, U8};
+use stylus_sdk::alloy_primitives::{keccak256, Address, B256, I128, I64, U16, U256, U64};
+use stylus_sdk::alloy_sol_types::{SolCall, SolError, SolEvent, SolValue};
 // alloc::vec is already available in test environment
 
+/// Distinct identity commitment for each test account
+fn identity_of(account: Address) -> B256 {
+    identity_commitment(&format!("{account}@example.com"), keccak256(account))
+}
+
 /// Happy paths
 #[test]
 fn test_voter_registration() {
@@ -18,11 +35,11 @@ fn test_voter_registration() {
     contract.constructor();
 
     let voter = Address::from([2u8; 20]);
-    let email = "test@example.com".to_string();
+    let identity = identity_commitment("test@example.com", B256::repeat_byte(0x5a));
 
     // Register voter
     vm.set_sender(voter);
-    let result = contract.register_voter(email.clone());
+    let result = contract.register_voter(identity, Vec::new().into());
     assert!(result.is_ok());
 
     // Voter registration is verified by successful registration call
@@ -43,7 +60,7 @@ fn test_create_session_with_proposals() {
     // Create session with initial proposals
     let name = "Test Session".to_string();
     let description = "A voting session".to_string();
-    let credits_per_voter = U8::from(100);
+    let credits_per_voter = U256::from(100);
     let duration = U64::from(3600);
 
     let initial_proposals = vec![
@@ -66,8 +83,11 @@ fn test_create_session_with_proposals() {
             name.clone(),
             description.clone(),
             credits_per_voter,
+            U64::ZERO,
             duration,
             initial_proposals.clone(),
+            B256::ZERO,
+            U16::from(1),
         )
         .unwrap();
 
@@ -79,19 +99,19 @@ fn test_create_session_with_proposals() {
     assert_eq!(session.0, name);
     assert_eq!(session.1, description);
     assert_eq!(session.4, credits_per_voter);
-    assert_eq!(session.5, true); // active
+    assert!(session.5); // active
     assert_eq!(session.6, admin); // creator
 
     // Check proposals were added
-    assert_eq!(session.7, U8::from(3)); // proposal_count should be 3
+    assert_eq!(session.7, U16::from(3)); // proposal_count should be 3
 
     // Verify each proposal using bulk query
-    let all_proposals = contract.get_session_proposals(session_id).unwrap();
+    let all_proposals = contract.get_session_proposals(session_id, U16::ZERO, U16::MAX).unwrap();
     assert_eq!(all_proposals.len(), initial_proposals.len());
 
     for (i, (expected_title, expected_desc)) in initial_proposals.iter().enumerate() {
-        let (id, title, description, vote_count) = &all_proposals[i];
-        assert_eq!(*id, U8::from((i + 1) as u8)); // id
+        let (id, title, description, vote_count, _, _) = &all_proposals[i];
+        assert_eq!(*id, U16::from((i + 1) as u8)); // id
         assert_eq!(*title, *expected_title); // title
         assert_eq!(*description, *expected_desc); // description
         assert_eq!(*vote_count, U64::ZERO); // vote_count
@@ -113,7 +133,7 @@ fn test_vote_in_session() {
     // Register voter
     vm.set_sender(voter);
     contract
-        .register_voter("voter@example.com".to_string())
+        .register_voter(identity_of(voter), Vec::new().into())
         .unwrap();
 
     // Create session with initial proposals
@@ -126,14 +146,17 @@ fn test_vote_in_session() {
         .create_session(
             "Test".to_string(),
             "Desc".to_string(),
-            U8::from(100),
+            U256::from(100),
+            U64::ZERO,
             U64::from(3600),
             proposals,
+            B256::ZERO,
+            U16::from(1),
         )
         .unwrap();
 
-    let proposal1_id = U8::from(1);
-    let proposal2_id = U8::from(2);
+    let proposal1_id = U16::from(1);
+    let proposal2_id = U16::from(2);
 
     // Vote on proposals
     vm.set_sender(voter);
@@ -144,7 +167,7 @@ fn test_vote_in_session() {
     assert!(result.is_ok());
 
     // Check proposal vote counts using bulk query
-    let all_proposals = contract.get_session_proposals(session_id).unwrap();
+    let all_proposals = contract.get_session_proposals(session_id, U16::ZERO, U16::MAX).unwrap();
 
     // Find proposal1 (id = 1) and check vote count
     let proposal1 = all_proposals.iter().find(|p| p.0 == proposal1_id).unwrap();
@@ -154,11 +177,246 @@ fn test_vote_in_session() {
     let proposal2 = all_proposals.iter().find(|p| p.0 == proposal2_id).unwrap();
     assert_eq!(proposal2.3, U64::from(1)); // vote_count
 
-    // // Check credits were deducted
-    // let remaining_credits = contract
-    //     .get_voter_session_credits(session_id, voter)
-    //     .unwrap();
-    // assert_eq!(remaining_credits, U8::from(95)); // 100 - 5
+    // Check credits were deducted
+    let (allocated, spent, remaining) = contract
+        .get_voter_session_credits(session_id, voter)
+        .unwrap();
+    assert_eq!(allocated, U256::from(100));
+    assert_eq!(spent, U256::from(5));
+    assert_eq!(remaining, U256::from(95)); // 100 - 5
+}
+
+#[test]
+fn test_revote_raise_charges_only_difference() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let voter = Address::from([2u8; 20]);
+    vm.set_sender(voter);
+    contract
+        .register_voter(identity_of(voter), Vec::new().into())
+        .unwrap();
+
+    vm.set_sender(admin);
+    let session_id = contract
+        .create_session(
+            "Test".to_string(),
+            "Desc".to_string(),
+            U256::from(20),
+            U64::ZERO,
+            U64::from(3600),
+            vec![
+                ("Proposal 1".to_string(), "Desc 1".to_string()),
+                ("Proposal 2".to_string(), "Desc 2".to_string()),
+            ],
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+
+    // 2 votes cost 4 credits, leaving 16
+    vm.set_sender(voter);
+    contract
+        .vote(session_id, vec![U16::from(1)], vec![U64::from(2)])
+        .unwrap();
+
+    // Raising to 4 votes costs 16 in total, so only 12 more are charged
+    contract
+        .vote(session_id, vec![U16::from(1)], vec![U64::from(4)])
+        .unwrap();
+
+    let proposals = contract.get_session_proposals(session_id, U16::ZERO, U16::MAX).unwrap();
+    assert_eq!(proposals[0].3, U64::from(4));
+
+    // 4 credits remain, so 3 votes (9 credits) elsewhere must fail
+    let result = contract.vote(session_id, vec![U16::from(2)], vec![U64::from(3)]);
+    assert!(matches!(
+        result,
+        Err(QuadraticVotingError::InsufficientCredits(
+            InsufficientCredits {}
+        ))
+    ));
+
+    // 2 votes (4 credits) still fit
+    contract
+        .vote(session_id, vec![U16::from(2)], vec![U64::from(2)])
+        .unwrap();
+}
+
+#[test]
+fn test_revote_lower_refunds_difference() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let voter = Address::from([2u8; 20]);
+    vm.set_sender(voter);
+    contract
+        .register_voter(identity_of(voter), Vec::new().into())
+        .unwrap();
+
+    vm.set_sender(admin);
+    let session_id = contract
+        .create_session(
+            "Test".to_string(),
+            "Desc".to_string(),
+            U256::from(10),
+            U64::ZERO,
+            U64::from(3600),
+            vec![
+                ("Proposal 1".to_string(), "Desc 1".to_string()),
+                ("Proposal 2".to_string(), "Desc 2".to_string()),
+            ],
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+
+    // 3 votes cost 9 credits, leaving 1
+    vm.set_sender(voter);
+    contract
+        .vote(session_id, vec![U16::from(1)], vec![U64::from(3)])
+        .unwrap();
+
+    // Lowering to 1 vote refunds 8 credits, leaving 9
+    contract
+        .vote(session_id, vec![U16::from(1)], vec![U64::from(1)])
+        .unwrap();
+
+    // The refunded credits can be spent on another proposal
+    contract
+        .vote(session_id, vec![U16::from(2)], vec![U64::from(3)])
+        .unwrap();
+
+    let proposals = contract.get_session_proposals(session_id, U16::ZERO, U16::MAX).unwrap();
+    assert_eq!(proposals[0].3, U64::from(1));
+    assert_eq!(proposals[1].3, U64::from(3));
+}
+
+#[test]
+fn test_revote_full_withdrawal() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let voter = Address::from([2u8; 20]);
+    vm.set_sender(voter);
+    contract
+        .register_voter(identity_of(voter), Vec::new().into())
+        .unwrap();
+
+    vm.set_sender(admin);
+    let session_id = contract
+        .create_session(
+            "Test".to_string(),
+            "Desc".to_string(),
+            U256::from(10),
+            U64::ZERO,
+            U64::from(3600),
+            vec![
+                ("Proposal 1".to_string(), "Desc 1".to_string()),
+                ("Proposal 2".to_string(), "Desc 2".to_string()),
+            ],
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+
+    vm.set_sender(voter);
+    contract
+        .vote(
+            session_id,
+            vec![U16::from(1), U16::from(2)],
+            vec![U64::from(1), U64::from(3)],
+        )
+        .unwrap();
+
+    // Withdrawing every vote refunds the full 10 credits
+    contract
+        .vote(
+            session_id,
+            vec![U16::from(1), U16::from(2)],
+            vec![U64::ZERO, U64::ZERO],
+        )
+        .unwrap();
+
+    let results = contract.get_session_results(session_id).unwrap();
+    assert_eq!(results.0, U16::from(0)); // no winner
+    assert_eq!(results.3, U64::from(0)); // total_votes
+
+    contract
+        .vote(session_id, vec![U16::from(2)], vec![U64::from(3)])
+        .unwrap();
+    let proposals = contract.get_session_proposals(session_id, U16::ZERO, U16::MAX).unwrap();
+    assert_eq!(proposals[1].3, U64::from(3));
+}
+
+#[test]
+fn test_spending_all_credits_does_not_refill() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let voter = Address::from([2u8; 20]);
+    vm.set_sender(voter);
+    contract
+        .register_voter(identity_of(voter), Vec::new().into())
+        .unwrap();
+
+    vm.set_sender(admin);
+    let session_id = contract
+        .create_session(
+            "Test".to_string(),
+            "Desc".to_string(),
+            U256::from(9),
+            U64::ZERO,
+            U64::from(3600),
+            vec![
+                ("Proposal 1".to_string(), "Desc 1".to_string()),
+                ("Proposal 2".to_string(), "Desc 2".to_string()),
+            ],
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+
+    // Spend exactly every credit
+    vm.set_sender(voter);
+    contract
+        .vote(session_id, vec![U16::from(1)], vec![U64::from(3)])
+        .unwrap();
+    let credits = contract
+        .get_voter_session_credits(session_id, voter)
+        .unwrap();
+    assert_eq!(credits, (U256::from(9), U256::from(9), U256::ZERO));
+
+    // A fully spent budget must not be granted again
+    let result = contract.vote(session_id, vec![U16::from(2)], vec![U64::from(1)]);
+    assert!(matches!(
+        result,
+        Err(QuadraticVotingError::InsufficientCredits(
+            InsufficientCredits {}
+        ))
+    ));
+
+    // A voter who never voted reports the full allocation
+    let credits = contract
+        .get_voter_session_credits(session_id, admin)
+        .unwrap();
+    assert_eq!(credits, (U256::from(9), U256::ZERO, U256::from(9)));
 }
 
 /// Unhappy paths
@@ -178,9 +436,12 @@ fn test_create_session_with_no_proposals() {
     let result = contract.create_session(
         "Test Session".to_string(),
         "Description".to_string(),
-        U8::from(100),
+        U256::from(100),
+        U64::ZERO,
         U64::from(3600),
         vec![], // Empty proposals
+        B256::ZERO,
+        U16::from(1),
     );
 
     // Should fail with InvalidProposalCount error
@@ -205,7 +466,7 @@ fn test_insufficient_credits() {
     // Register voter
     vm.set_sender(voter);
     contract
-        .register_voter("voter@example.com".to_string())
+        .register_voter(identity_of(voter), Vec::new().into())
         .unwrap();
 
     // Create session with limited credits and a proposal
@@ -214,13 +475,16 @@ fn test_insufficient_credits() {
         .create_session(
             "Test".to_string(),
             "Desc".to_string(),
-            U8::from(5),
+            U256::from(5),
+            U64::ZERO,
             U64::from(3600),
             vec![("Proposal".to_string(), "Desc".to_string())],
+            B256::ZERO,
+            U16::from(1),
         )
         .unwrap();
 
-    let proposal_id = U8::from(1);
+    let proposal_id = U16::from(1);
 
     // Try to vote with more credits than available (3² = 9 > 5)
     vm.set_sender(voter);
@@ -247,7 +511,7 @@ fn test_session_not_active() {
     // Register voter
     vm.set_sender(voter);
     contract
-        .register_voter("voter@example.com".to_string())
+        .register_voter(identity_of(voter), Vec::new().into())
         .unwrap();
 
     // Create session with proposal
@@ -256,16 +520,19 @@ fn test_session_not_active() {
         .create_session(
             "Test".to_string(),
             "Desc".to_string(),
-            U8::from(100),
-            U64::from(1), // 1 block duration
+            U256::from(100),
+            U64::ZERO,
+            U64::from(1), // 1 second duration
             vec![("Proposal".to_string(), "Desc".to_string())],
+            B256::ZERO,
+            U16::from(1),
         )
         .unwrap();
 
-    let proposal_id = U8::from(1);
+    let proposal_id = U16::from(1);
 
-    // Advance block number to expire session
-    vm.set_block_number(100);
+    // Advance time to expire session
+    vm.set_block_timestamp(100);
 
     // Try to vote on expired session
     vm.set_sender(voter);
@@ -292,13 +559,16 @@ fn test_voter_not_registered() {
         .create_session(
             "Test".to_string(),
             "Desc".to_string(),
-            U8::from(100),
+            U256::from(100),
+            U64::ZERO,
             U64::from(3600),
             vec![("Proposal".to_string(), "Desc".to_string())],
+            B256::ZERO,
+            U16::from(1),
         )
         .unwrap();
 
-    let proposal_id = U8::from(1);
+    let proposal_id = U16::from(1);
 
     // Try to vote without being registered
     vm.set_sender(voter);
@@ -338,7 +608,7 @@ fn test_session_results() {
     // Register voter
     vm.set_sender(voter);
     contract
-        .register_voter("voter@example.com".to_string())
+        .register_voter(identity_of(voter), Vec::new().into())
         .unwrap();
 
     // Create session with proposals
@@ -347,33 +617,36 @@ fn test_session_results() {
         .create_session(
             "Test".to_string(),
             "Desc".to_string(),
-            U8::from(100),
+            U256::from(100),
+            U64::ZERO,
             U64::from(3600),
             vec![
                 ("Proposal 1".to_string(), "Desc 1".to_string()),
                 ("Proposal 2".to_string(), "Desc 2".to_string()),
             ],
+            B256::ZERO,
+            U16::from(1),
         )
         .unwrap();
 
     // Check initial results (no votes yet)
     let results = contract.get_session_results(session_id).unwrap();
-    assert_eq!(results.0, U8::from(0)); // winner_id (0 = no winner yet)
-    assert_eq!(results.1, U8::from(2)); // proposal_count
+    assert_eq!(results.0, U16::from(0)); // winner_id (0 = no winner yet)
+    assert_eq!(results.1, U16::from(2)); // proposal_count
     assert_eq!(results.2, U64::from(0)); // max_votes
     assert_eq!(results.3, U64::from(0)); // total_votes
 
     // Vote on proposals
     vm.set_sender(voter);
-    contract.vote(session_id, vec![U8::from(1), U8::from(2)], vec![U64::from(2), U64::from(1)]).unwrap();
+    contract.vote(session_id, vec![U16::from(1), U16::from(2)], vec![U64::from(2), U64::from(1)]).unwrap();
 
     // Check results after voting
     let results = contract.get_session_results(session_id).unwrap();
-    assert_eq!(results.0, U8::from(1)); // winner_id (proposal 1 has more votes)
-    assert_eq!(results.1, U8::from(2)); // proposal_count
+    assert_eq!(results.0, U16::from(1)); // winner_id (proposal 1 has more votes)
+    assert_eq!(results.1, U16::from(2)); // proposal_count
 
     // Check actual proposal data to debug
-    let proposals = contract.get_session_proposals(session_id).unwrap();
+    let proposals = contract.get_session_proposals(session_id, U16::ZERO, U16::MAX).unwrap();
     let proposal1_votes = proposals[0].3; // proposal 1 vote count
     let proposal2_votes = proposals[1].3; // proposal 2 vote count
 
@@ -382,5 +655,4883 @@ fn test_session_results() {
 
     assert_eq!(results.2, U64::from(2)); // max_votes (highest vote intensity)
     assert_eq!(results.3, U64::from(3)); // total_votes (2 + 1)
+
+    // Withdrawing from the leader hands the lead to proposal 2
+    contract.vote(session_id, vec![U16::from(1)], vec![U64::ZERO]).unwrap();
+    let results = contract.get_session_results(session_id).unwrap();
+    assert_eq!(results.0, U16::from(2));
+    assert_eq!(results.2, U64::from(1));
+    assert_eq!(results.3, U64::from(1));
+}
+
+
+#[test]
+fn test_vote_rejects_unknown_proposal_ids() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let voter = Address::from([2u8; 20]);
+    vm.set_sender(voter);
+    contract
+        .register_voter(identity_of(voter), Vec::new().into())
+        .unwrap();
+
+    vm.set_sender(admin);
+    let session_id = contract
+        .create_session(
+            "Test".to_string(),
+            "Desc".to_string(),
+            U256::from(100),
+            U64::ZERO,
+            U64::from(3600),
+            vec![
+                ("Proposal 1".to_string(), "Desc 1".to_string()),
+                ("Proposal 2".to_string(), "Desc 2".to_string()),
+            ],
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+
+    vm.set_sender(voter);
+    for proposal_id in [0u8, 3, 255] {
+        let result = contract.vote(
+            session_id,
+            vec![U16::from(1), U16::from(proposal_id)],
+            vec![U64::from(1), U64::from(1)],
+        );
+        assert!(matches!(
+            result,
+            Err(QuadraticVotingError::InvalidProposal(InvalidProposal {}))
+        ));
+    }
+
+    // Nothing from the rejected batches was applied
+    let proposals = contract.get_session_proposals(session_id, U16::ZERO, U16::MAX).unwrap();
+    assert_eq!(proposals[0].3, U64::ZERO);
+}
+
+#[test]
+fn test_vote_rejects_duplicate_proposal_ids() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let voter = Address::from([2u8; 20]);
+    vm.set_sender(voter);
+    contract
+        .register_voter(identity_of(voter), Vec::new().into())
+        .unwrap();
+
+    vm.set_sender(admin);
+    let session_id = contract
+        .create_session(
+            "Test".to_string(),
+            "Desc".to_string(),
+            U256::from(100),
+            U64::ZERO,
+            U64::from(3600),
+            vec![
+                ("Proposal 1".to_string(), "Desc 1".to_string()),
+                ("Proposal 2".to_string(), "Desc 2".to_string()),
+            ],
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+
+    vm.set_sender(voter);
+    let result = contract.vote(
+        session_id,
+        vec![U16::from(1), U16::from(2), U16::from(1)],
+        vec![U64::from(1), U64::from(1), U64::from(5)],
+    );
+    assert!(matches!(
+        result,
+        Err(QuadraticVotingError::InvalidProposal(InvalidProposal {}))
+    ));
+
+    let credits = contract
+        .get_voter_session_credits(session_id, voter)
+        .unwrap();
+    assert_eq!(credits.1, U256::ZERO);
 }
 
+/// Randomized batches
+#[test]
+fn test_random_vote_batches_keep_tally_consistent() {
+    use stylus_sdk::testing::*;
+
+    // Small deterministic xorshift generator so failures are reproducible
+    let mut seed: u64 = 0x9e37_79b9_7f4a_7c15;
+    let mut next = move |bound: u64| {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        seed % bound
+    };
+
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let voters: Vec<Address> = (10u8..14).map(|b| Address::from([b; 20])).collect();
+    for &voter in &voters {
+        vm.set_sender(voter);
+        contract
+            .register_voter(identity_of(voter), Vec::new().into())
+            .unwrap();
+    }
+
+    const PROPOSALS: usize = 5;
+    vm.set_sender(admin);
+    let session_id = contract
+        .create_session(
+            "Fuzz".to_string(),
+            "Desc".to_string(),
+            U256::from(60),
+            U64::ZERO,
+            U64::from(3600),
+            (1..=PROPOSALS)
+                .map(|i| (format!("Proposal {i}"), "Desc".to_string()))
+                .collect(),
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+
+    // Model of each voter's current allocation, indexed by proposal id - 1
+    let mut model = vec![[0u64; PROPOSALS]; voters.len()];
+
+    for _ in 0..500 {
+        let v = next(voters.len() as u64) as usize;
+        let batch_len = 1 + next(4) as usize;
+        // Ids 0..=PROPOSALS+1 so unknown ids and repeats are both generated
+        let ids: Vec<u8> = (0..batch_len)
+            .map(|_| next(PROPOSALS as u64 + 2) as u8)
+            .collect();
+        let counts: Vec<u64> = (0..batch_len).map(|_| next(6)).collect();
+
+        let invalid_id = ids
+            .iter()
+            .enumerate()
+            .any(|(i, &id)| id == 0 || id as usize > PROPOSALS || ids[..i].contains(&id));
+
+        let mut next_alloc = model[v];
+        if !invalid_id {
+            for (&id, &count) in ids.iter().zip(counts.iter()) {
+                next_alloc[id as usize - 1] = count;
+            }
+        }
+        let cost: u64 = next_alloc.iter().map(|c| c * c).sum();
+
+        vm.set_sender(voters[v]);
+        let result = contract.vote(
+            session_id,
+            ids.iter().map(|&id| U16::from(id)).collect(),
+            counts.iter().map(|&c| U64::from(c)).collect(),
+        );
+
+        if invalid_id {
+            assert!(matches!(
+                result,
+                Err(QuadraticVotingError::InvalidProposal(_))
+            ));
+        } else if cost > 60 {
+            assert!(matches!(
+                result,
+                Err(QuadraticVotingError::InsufficientCredits(_))
+            ));
+        } else {
+            assert!(result.is_ok());
+            model[v] = next_alloc;
+        }
+
+        // Tally invariant: each proposal's vote_count is the sum of every voter's votes
+        let proposals = contract.get_session_proposals(session_id, U16::ZERO, U16::MAX).unwrap();
+        for (p, proposal) in proposals.iter().enumerate() {
+            let expected: u64 = model.iter().map(|alloc| alloc[p]).sum();
+            assert_eq!(proposal.3, U64::from(expected));
+            assert_eq!(proposal.4, U64::ZERO);
+        }
+
+        // Incrementally tracked results match a full scan: most votes, lowest id on ties
+        let totals: Vec<u64> = (0..PROPOSALS)
+            .map(|p| model.iter().map(|alloc| alloc[p]).sum())
+            .collect();
+        let max_votes = *totals.iter().max().unwrap();
+        let winner = if max_votes == 0 {
+            0
+        } else {
+            totals.iter().position(|&t| t == max_votes).unwrap() + 1
+        };
+        let (winner_id, _, max, total, _) = contract.get_session_results(session_id).unwrap();
+        assert_eq!(winner_id, U16::from(winner));
+        assert_eq!(max, U64::from(max_votes));
+        assert_eq!(total, U64::from(totals.iter().sum::<u64>()));
+
+        let spent = contract
+            .get_voter_session_credits(session_id, voters[v])
+            .unwrap()
+            .1;
+        let expected_spent: u64 = model[v].iter().map(|c| c * c).sum();
+        assert_eq!(spent, U256::from(expected_spent));
+    }
+}
+
+#[test]
+fn test_random_signed_intensities_keep_tally_consistent() {
+    use stylus_sdk::testing::*;
+
+    // Small deterministic xorshift generator so failures are reproducible
+    let mut seed: u64 = 0x9e37_79b9_7f4a_7c15;
+    let mut next = move |bound: u64| {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        seed % bound
+    };
+
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let voters: Vec<Address> = (10u8..14).map(|b| Address::from([b; 20])).collect();
+    for &voter in &voters {
+        vm.set_sender(voter);
+        contract
+            .register_voter(identity_of(voter), Vec::new().into())
+            .unwrap();
+    }
+
+    const PROPOSALS: usize = 5;
+    vm.set_sender(admin);
+    let session_id = contract
+        .create_session(
+            "Fuzz".to_string(),
+            "Desc".to_string(),
+            U256::from(60),
+            U64::ZERO,
+            U64::from(3600),
+            (1..=PROPOSALS)
+                .map(|i| (format!("Proposal {i}"), "Desc".to_string()))
+                .collect(),
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+
+    // Model of each voter's current signed allocation, indexed by proposal id - 1
+    let mut model = vec![[0i64; PROPOSALS]; voters.len()];
+
+    for _ in 0..500 {
+        let v = next(voters.len() as u64) as usize;
+        let batch_len = 1 + next(4) as usize;
+        // Ids 0..=PROPOSALS+1 so unknown ids and repeats are both generated
+        let ids: Vec<u8> = (0..batch_len)
+            .map(|_| next(PROPOSALS as u64 + 2) as u8)
+            .collect();
+        // Intensities -5..=5, negative ones opposing the proposal
+        let counts: Vec<i64> = (0..batch_len).map(|_| next(11) as i64 - 5).collect();
+
+        let invalid_id = ids
+            .iter()
+            .enumerate()
+            .any(|(i, &id)| id == 0 || id as usize > PROPOSALS || ids[..i].contains(&id));
+
+        let mut next_alloc = model[v];
+        if !invalid_id {
+            for (&id, &count) in ids.iter().zip(counts.iter()) {
+                next_alloc[id as usize - 1] = count;
+            }
+        }
+        let cost: i64 = next_alloc.iter().map(|c| c * c).sum();
+
+        vm.set_sender(voters[v]);
+        let result = contract.vote_with_intensities(
+            session_id,
+            ids.iter().map(|&id| U16::from(id)).collect(),
+            counts.iter().map(|&c| I64::try_from(c).unwrap()).collect(),
+        );
+
+        if invalid_id {
+            assert!(matches!(
+                result,
+                Err(QuadraticVotingError::InvalidProposal(_))
+            ));
+        } else if cost > 60 {
+            assert!(matches!(
+                result,
+                Err(QuadraticVotingError::InsufficientCredits(_))
+            ));
+        } else {
+            assert!(result.is_ok());
+            model[v] = next_alloc;
+        }
+
+        // Tally invariant: support and opposition are the sums of every voter's votes
+        let support: Vec<i64> = (0..PROPOSALS)
+            .map(|p| model.iter().map(|alloc| alloc[p].max(0)).sum())
+            .collect();
+        let opposition: Vec<i64> = (0..PROPOSALS)
+            .map(|p| model.iter().map(|alloc| (-alloc[p]).max(0)).sum())
+            .collect();
+        let proposals = contract.get_session_proposals(session_id, U16::ZERO, U16::MAX).unwrap();
+        for (p, proposal) in proposals.iter().enumerate() {
+            assert_eq!(proposal.3, U64::from(support[p] as u64));
+            assert_eq!(proposal.4, U64::from(opposition[p] as u64));
+            assert_eq!(proposal.5, I128::try_from(support[p] - opposition[p]).unwrap());
+        }
+
+        // Incrementally tracked results match a full scan: highest positive net, lowest id on ties
+        let nets: Vec<i64> = (0..PROPOSALS).map(|p| support[p] - opposition[p]).collect();
+        let max_net = (*nets.iter().max().unwrap()).max(0);
+        let winner = if max_net == 0 {
+            0
+        } else {
+            nets.iter().position(|&n| n == max_net).unwrap() + 1
+        };
+        let (winner_id, _, max, total, _) = contract.get_session_results(session_id).unwrap();
+        assert_eq!(winner_id, U16::from(winner));
+        assert_eq!(max, U64::from(max_net as u64));
+        let total_votes: i64 = support.iter().chain(opposition.iter()).sum();
+        assert_eq!(total, U64::from(total_votes as u64));
+
+        let spent = contract
+            .get_voter_session_credits(session_id, voters[v])
+            .unwrap()
+            .1;
+        let expected_spent: i64 = model[v].iter().map(|c| c * c).sum();
+        assert_eq!(spent, U256::from(expected_spent as u64));
+    }
+}
+
+#[test]
+fn test_large_intensities_do_not_wrap() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let voter = Address::from([2u8; 20]);
+    vm.set_sender(voter);
+    contract
+        .register_voter(identity_of(voter), Vec::new().into())
+        .unwrap();
+
+    vm.set_sender(admin);
+    let session_id = contract
+        .create_session(
+            "Test".to_string(),
+            "Desc".to_string(),
+            U256::from(100),
+            U64::ZERO,
+            U64::from(3600),
+            vec![("Proposal".to_string(), "Desc".to_string())],
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+
+    // 16² = 256 used to truncate to 0 in a uint8 and slip through the check
+    vm.set_sender(voter);
+    for intensity in [16u64, 1 << 32, u64::MAX] {
+        let result = contract.vote(session_id, vec![U16::from(1)], vec![U64::from(intensity)]);
+        assert!(matches!(
+            result,
+            Err(QuadraticVotingError::InsufficientCredits(
+                InsufficientCredits {}
+            ))
+        ));
+    }
+}
+
+#[test]
+fn test_wide_credit_budget() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let voter = Address::from([2u8; 20]);
+    vm.set_sender(voter);
+    contract
+        .register_voter(identity_of(voter), Vec::new().into())
+        .unwrap();
+
+    vm.set_sender(admin);
+    let session_id = contract
+        .create_session(
+            "Test".to_string(),
+            "Desc".to_string(),
+            U256::MAX,
+            U64::ZERO,
+            U64::from(3600),
+            vec![
+                ("Proposal 1".to_string(), "Desc 1".to_string()),
+                ("Proposal 2".to_string(), "Desc 2".to_string()),
+            ],
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+
+    vm.set_sender(voter);
+    contract
+        .vote(
+            session_id,
+            vec![U16::from(1), U16::from(2)],
+            vec![U64::MAX, U64::from(1_000_000)],
+        )
+        .unwrap();
+
+    let max_cost = U256::from(u64::MAX) * U256::from(u64::MAX);
+    let (allocated, spent, remaining) = contract
+        .get_voter_session_credits(session_id, voter)
+        .unwrap();
+    assert_eq!(allocated, U256::MAX);
+    assert_eq!(spent, max_cost + U256::from(1_000_000_000_000u64));
+    assert_eq!(remaining, U256::MAX - spent);
+
+    // A second maximal voter would overflow the proposal's uint64 tally
+    let other = Address::from([3u8; 20]);
+    vm.set_sender(other);
+    contract
+        .register_voter(identity_of(other), Vec::new().into())
+        .unwrap();
+    let result = contract.vote(session_id, vec![U16::from(1)], vec![U64::MAX]);
+    assert!(matches!(
+        result,
+        Err(QuadraticVotingError::InvalidVoteCount(_))
+    ));
+}
+
+#[test]
+fn test_scheduled_session_window() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let voter = Address::from([2u8; 20]);
+    vm.set_sender(voter);
+    contract
+        .register_voter(identity_of(voter), Vec::new().into())
+        .unwrap();
+
+    // Session opens at t=1_000 and runs for an hour
+    vm.set_block_timestamp(500);
+    vm.set_sender(admin);
+    let session_id = contract
+        .create_session(
+            "Test".to_string(),
+            "Desc".to_string(),
+            U256::from(100),
+            U64::from(1_000),
+            U64::from(3600),
+            vec![("Proposal".to_string(), "Desc".to_string())],
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+
+    let session = contract.get_session(session_id).unwrap();
+    assert_eq!(session.2, U256::from(1_000)); // start_time
+    assert_eq!(session.3, U256::from(4_600)); // end_time
+
+    // Too early
+    vm.set_sender(voter);
+    let result = contract.vote(session_id, vec![U16::from(1)], vec![U64::from(1)]);
+    assert!(matches!(
+        result,
+        Err(QuadraticVotingError::InvalidSession(InvalidSession {}))
+    ));
+
+    // Block numbers no longer affect the window
+    vm.set_block_number(1_000_000);
+    vm.set_block_timestamp(1_000);
+    contract
+        .vote(session_id, vec![U16::from(1)], vec![U64::from(1)])
+        .unwrap();
+
+    vm.set_block_timestamp(4_599);
+    contract
+        .vote(session_id, vec![U16::from(1)], vec![U64::from(2)])
+        .unwrap();
+
+    // end_time itself is already closed
+    vm.set_block_timestamp(4_600);
+    let result = contract.vote(session_id, vec![U16::from(1)], vec![U64::from(3)]);
+    assert!(matches!(
+        result,
+        Err(QuadraticVotingError::InvalidSession(InvalidSession {}))
+    ));
+}
+
+#[test]
+fn test_create_session_with_past_start_time() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    vm.set_block_timestamp(10_000);
+    let result = contract.create_session(
+        "Test".to_string(),
+        "Desc".to_string(),
+        U256::from(100),
+        U64::from(9_999),
+        U64::from(3600),
+        vec![("Proposal".to_string(), "Desc".to_string())],
+        B256::ZERO,
+        U16::from(1),
+    );
+    assert!(matches!(
+        result,
+        Err(QuadraticVotingError::InvalidSession(InvalidSession {}))
+    ));
+
+    // Zero means "start now"
+    let session_id = contract
+        .create_session(
+            "Test".to_string(),
+            "Desc".to_string(),
+            U256::from(100),
+            U64::ZERO,
+            U64::from(3600),
+            vec![("Proposal".to_string(), "Desc".to_string())],
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+    let session = contract.get_session(session_id).unwrap();
+    assert_eq!(session.2, U256::from(10_000));
+    assert_eq!(session.3, U256::from(13_600));
+}
+
+/// Session lifecycle
+#[test]
+fn test_close_session_early() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let voter = Address::from([2u8; 20]);
+    vm.set_sender(voter);
+    contract
+        .register_voter(identity_of(voter), Vec::new().into())
+        .unwrap();
+
+    vm.set_sender(admin);
+    let session_id = contract
+        .create_session(
+            "Test".to_string(),
+            "Desc".to_string(),
+            U256::from(100),
+            U64::ZERO,
+            U64::from(3600),
+            vec![("Proposal".to_string(), "Desc".to_string())],
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+
+    vm.set_sender(voter);
+    contract
+        .vote(session_id, vec![U16::from(1)], vec![U64::from(2)])
+        .unwrap();
+
+    // Only the admin or creator may close
+    let result = contract.close_session(session_id);
+    assert!(matches!(result, Err(QuadraticVotingError::Unauthorized(_))));
+
+    vm.set_block_timestamp(60);
+    vm.set_sender(admin);
+    contract.close_session(session_id).unwrap();
+
+    let session = contract.get_session(session_id).unwrap();
+    assert!(!session.5); // active
+    assert_eq!(session.3, U256::from(60)); // end_time moved to the close time
+
+    let logs = vm.get_emitted_logs();
+    let (topics, _) = logs.last().unwrap();
+    assert_eq!(topics[0], SessionClosed::SIGNATURE_HASH);
+
+    vm.set_sender(voter);
+    let result = contract.vote(session_id, vec![U16::from(1)], vec![U64::from(1)]);
+    assert!(matches!(
+        result,
+        Err(QuadraticVotingError::InvalidSession(InvalidSession {}))
+    ));
+
+    // Results of a closed session stay available
+    let results = contract.get_session_results(session_id).unwrap();
+    assert_eq!(results.0, U16::from(1));
+
+    // A closed session can neither be closed again nor extended
+    vm.set_sender(admin);
+    assert!(contract.close_session(session_id).is_err());
+    assert!(contract.extend_session(session_id, U64::from(10_000)).is_err());
+}
+
+#[test]
+fn test_cancel_session_voids_results() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let voter = Address::from([2u8; 20]);
+    vm.set_sender(voter);
+    contract
+        .register_voter(identity_of(voter), Vec::new().into())
+        .unwrap();
+
+    vm.set_sender(admin);
+    let session_id = contract
+        .create_session(
+            "Test".to_string(),
+            "Desc".to_string(),
+            U256::from(100),
+            U64::ZERO,
+            U64::from(3600),
+            vec![("Proposal".to_string(), "Desc".to_string())],
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+
+    vm.set_sender(voter);
+    contract
+        .vote(session_id, vec![U16::from(1)], vec![U64::from(2)])
+        .unwrap();
+
+    vm.set_sender(admin);
+    contract.cancel_session(session_id).unwrap();
+
+    let logs = vm.get_emitted_logs();
+    let (topics, _) = logs.last().unwrap();
+    assert_eq!(topics[0], SessionCancelled::SIGNATURE_HASH);
+
+    assert_eq!(
+        contract.get_session_status(session_id).unwrap(),
+        (false, false, true)
+    );
+    assert!(matches!(
+        contract.get_session_results(session_id),
+        Err(QuadraticVotingError::InvalidSession(InvalidSession {}))
+    ));
+
+    vm.set_sender(voter);
+    assert!(contract
+        .vote(session_id, vec![U16::from(1)], vec![U64::from(1)])
+        .is_err());
+
+    vm.set_sender(admin);
+    assert!(contract.cancel_session(session_id).is_err());
+}
+
+#[test]
+fn test_extend_session_reopens_expired_window() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let voter = Address::from([2u8; 20]);
+    vm.set_sender(voter);
+    contract
+        .register_voter(identity_of(voter), Vec::new().into())
+        .unwrap();
+
+    vm.set_sender(admin);
+    let session_id = contract
+        .create_session(
+            "Test".to_string(),
+            "Desc".to_string(),
+            U256::from(100),
+            U64::ZERO,
+            U64::from(100),
+            vec![("Proposal".to_string(), "Desc".to_string())],
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+
+    vm.set_block_timestamp(150);
+    vm.set_sender(voter);
+    assert!(contract
+        .vote(session_id, vec![U16::from(1)], vec![U64::from(1)])
+        .is_err());
+
+    // The new end must be in the future and later than the current end
+    vm.set_sender(admin);
+    assert!(contract.extend_session(session_id, U64::from(100)).is_err());
+    assert!(contract.extend_session(session_id, U64::from(120)).is_err());
+    contract.extend_session(session_id, U64::from(500)).unwrap();
+
+    let logs = vm.get_emitted_logs();
+    let (topics, data) = logs.last().unwrap();
+    assert_eq!(topics[0], SessionExtended::SIGNATURE_HASH);
+    let event = SessionExtended::decode_raw_log(topics.clone(), data, true).unwrap();
+    assert_eq!(event.end_time, U256::from(500));
+
+    vm.set_sender(voter);
+    contract
+        .vote(session_id, vec![U16::from(1)], vec![U64::from(1)])
+        .unwrap();
+    assert_eq!(contract.get_session(session_id).unwrap().3, U256::from(500));
+}
+
+#[test]
+fn test_pause_and_resume_session() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let voter = Address::from([2u8; 20]);
+    vm.set_sender(voter);
+    contract
+        .register_voter(identity_of(voter), Vec::new().into())
+        .unwrap();
+
+    vm.set_sender(admin);
+    let session_id = contract
+        .create_session(
+            "Test".to_string(),
+            "Desc".to_string(),
+            U256::from(100),
+            U64::ZERO,
+            U64::from(3600),
+            vec![("Proposal".to_string(), "Desc".to_string())],
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+
+    vm.set_sender(voter);
+    assert!(matches!(
+        contract.pause_session(session_id),
+        Err(QuadraticVotingError::Unauthorized(_))
+    ));
+
+    vm.set_sender(admin);
+    contract.pause_session(session_id).unwrap();
+    assert!(contract.pause_session(session_id).is_err());
+    assert_eq!(
+        contract.get_session_status(session_id).unwrap(),
+        (true, true, false)
+    );
+
+    let logs = vm.get_emitted_logs();
+    let (topics, data) = logs.last().unwrap();
+    assert_eq!(topics[0], SessionPaused::SIGNATURE_HASH);
+    assert!(SessionPaused::decode_raw_log(topics.clone(), data, true).unwrap().paused);
+
+    vm.set_sender(voter);
+    assert!(matches!(
+        contract.vote(session_id, vec![U16::from(1)], vec![U64::from(1)]),
+        Err(QuadraticVotingError::InvalidSession(InvalidSession {}))
+    ));
+
+    vm.set_sender(admin);
+    contract.resume_session(session_id).unwrap();
+    assert!(contract.resume_session(session_id).is_err());
+
+    vm.set_sender(voter);
+    contract
+        .vote(session_id, vec![U16::from(1)], vec![U64::from(1)])
+        .unwrap();
+}
+
+/// Access control
+#[test]
+fn test_role_identifiers() {
+    assert_eq!(ADMIN_ROLE, B256::ZERO);
+    assert_eq!(SESSION_CREATOR_ROLE, keccak256("SESSION_CREATOR_ROLE"));
+    assert_eq!(VOTER_REGISTRAR_ROLE, keccak256("VOTER_REGISTRAR_ROLE"));
+    assert_eq!(PAUSER_ROLE, keccak256("PAUSER_ROLE"));
+}
+
+#[test]
+fn test_deployer_holds_every_role() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    for role in [ADMIN_ROLE, SESSION_CREATOR_ROLE, VOTER_REGISTRAR_ROLE, PAUSER_ROLE] {
+        assert!(contract.has_role(role, admin));
+        assert!(!contract.has_role(role, Address::from([2u8; 20])));
+    }
+    assert_eq!(contract.get_admin(), (admin, Address::ZERO));
+}
+
+#[test]
+fn test_grant_and_revoke_session_creator() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let creator = Address::from([3u8; 20]);
+    let create = |contract: &mut QuadraticVoting| {
+        contract.create_session(
+            "Test".to_string(),
+            "Desc".to_string(),
+            U256::from(100),
+            U64::ZERO,
+            U64::from(3600),
+            vec![("Proposal".to_string(), "Desc".to_string())],
+            B256::ZERO,
+            U16::from(1),
+        )
+    };
+
+    vm.set_sender(creator);
+    assert!(matches!(
+        create(&mut contract),
+        Err(QuadraticVotingError::Unauthorized(_))
+    ));
+
+    // Only admins may grant
+    assert!(matches!(
+        contract.grant_role(SESSION_CREATOR_ROLE, creator),
+        Err(QuadraticVotingError::Unauthorized(_))
+    ));
+
+    vm.set_sender(admin);
+    contract.grant_role(SESSION_CREATOR_ROLE, creator).unwrap();
+    let logs = vm.get_emitted_logs();
+    let (topics, data) = logs.last().unwrap();
+    assert_eq!(topics[0], RoleGranted::SIGNATURE_HASH);
+    let event = RoleGranted::decode_raw_log(topics.clone(), data, true).unwrap();
+    assert_eq!(event.role, SESSION_CREATOR_ROLE);
+    assert_eq!(event.account, creator);
+    assert_eq!(event.sender, admin);
+
+    vm.set_sender(creator);
+    let session_id = create(&mut contract).unwrap();
+
+    // The creator manages its own session without holding ADMIN_ROLE
+    contract.close_session(session_id).unwrap();
+
+    vm.set_sender(admin);
+    contract.revoke_role(SESSION_CREATOR_ROLE, creator).unwrap();
+    let logs = vm.get_emitted_logs();
+    let (topics, _) = logs.last().unwrap();
+    assert_eq!(topics[0], RoleRevoked::SIGNATURE_HASH);
+
+    vm.set_sender(creator);
+    assert!(matches!(
+        create(&mut contract),
+        Err(QuadraticVotingError::Unauthorized(_))
+    ));
+}
+
+#[test]
+fn test_pauser_role_can_pause_any_session() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let session_id = contract
+        .create_session(
+            "Test".to_string(),
+            "Desc".to_string(),
+            U256::from(100),
+            U64::ZERO,
+            U64::from(3600),
+            vec![("Proposal".to_string(), "Desc".to_string())],
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+
+    let pauser = Address::from([4u8; 20]);
+    contract.grant_role(PAUSER_ROLE, pauser).unwrap();
+
+    vm.set_sender(pauser);
+    contract.pause_session(session_id).unwrap();
+    contract.resume_session(session_id).unwrap();
+
+    // Pausing does not grant the other lifecycle powers
+    assert!(matches!(
+        contract.cancel_session(session_id),
+        Err(QuadraticVotingError::Unauthorized(_))
+    ));
+
+    contract.renounce_role(PAUSER_ROLE).unwrap();
+    assert!(!contract.has_role(PAUSER_ROLE, pauser));
+    assert!(matches!(
+        contract.pause_session(session_id),
+        Err(QuadraticVotingError::Unauthorized(_))
+    ));
+}
+
+#[test]
+fn test_admin_role_only_moves_by_two_step_transfer() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let new_admin = Address::from([5u8; 20]);
+    let outsider = Address::from([6u8; 20]);
+
+    assert!(contract.grant_role(ADMIN_ROLE, new_admin).is_err());
+    assert!(contract.revoke_role(ADMIN_ROLE, admin).is_err());
+    assert!(contract.renounce_role(ADMIN_ROLE).is_err());
+
+    vm.set_sender(outsider);
+    assert!(matches!(
+        contract.transfer_admin(outsider),
+        Err(QuadraticVotingError::Unauthorized(_))
+    ));
+
+    vm.set_sender(admin);
+    contract.transfer_admin(new_admin).unwrap();
+    assert_eq!(contract.get_admin(), (admin, new_admin));
+
+    // Nobody but the pending admin can accept
+    vm.set_sender(outsider);
+    assert!(matches!(
+        contract.accept_admin(),
+        Err(QuadraticVotingError::Unauthorized(_))
+    ));
+
+    vm.set_sender(new_admin);
+    contract.accept_admin().unwrap();
+    assert_eq!(contract.get_admin(), (new_admin, Address::ZERO));
+    assert!(contract.has_role(ADMIN_ROLE, new_admin));
+    assert!(!contract.has_role(ADMIN_ROLE, admin));
+
+    // The previous admin keeps its other roles until revoked
+    assert!(contract.has_role(SESSION_CREATOR_ROLE, admin));
+    contract.revoke_role(SESSION_CREATOR_ROLE, admin).unwrap();
+    assert!(!contract.has_role(SESSION_CREATOR_ROLE, admin));
+
+    vm.set_sender(admin);
+    assert!(contract.grant_role(PAUSER_ROLE, outsider).is_err());
+}
+
+/// Voter registration
+#[test]
+fn test_approval_mode_requires_registrar() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    assert_eq!(contract.get_registration_mode(), REGISTRATION_OPEN);
+    contract
+        .set_registration_mode(REGISTRATION_APPROVAL)
+        .unwrap();
+    assert!(matches!(
+        contract.set_registration_mode(7),
+        Err(QuadraticVotingError::InvalidMode(_))
+    ));
+
+    let session_id = contract
+        .create_session(
+            "Test".to_string(),
+            "Desc".to_string(),
+            U256::from(100),
+            U64::ZERO,
+            U64::from(3600),
+            vec![("Proposal".to_string(), "Desc".to_string())],
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+
+    let voter = Address::from([2u8; 20]);
+    vm.set_sender(voter);
+    contract
+        .register_voter(identity_of(voter), Vec::new().into())
+        .unwrap();
+    assert_eq!(contract.get_voter_status(voter), (false, true, false));
+
+    // A pending request cannot vote or be filed twice
+    assert!(matches!(
+        contract.vote(session_id, vec![U16::from(1)], vec![U64::from(1)]),
+        Err(QuadraticVotingError::VoterNotRegistered(_))
+    ));
+    assert!(contract
+        .register_voter(identity_of(voter), Vec::new().into())
+        .is_err());
+
+    // Only registrars approve, and only pending addresses
+    assert!(matches!(
+        contract.approve_voters(vec![voter]),
+        Err(QuadraticVotingError::Unauthorized(_))
+    ));
+    vm.set_sender(admin);
+    assert!(matches!(
+        contract.approve_voters(vec![voter, Address::from([9u8; 20])]),
+        Err(QuadraticVotingError::VoterNotRegistered(_))
+    ));
+    contract.approve_voters(vec![voter]).unwrap();
+    assert_eq!(contract.get_voter_status(voter), (true, false, false));
+
+    vm.set_sender(voter);
+    contract
+        .vote(session_id, vec![U16::from(1)], vec![U64::from(1)])
+        .unwrap();
+}
+
+#[test]
+fn test_allowlist_mode_batch_registration() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+    contract
+        .set_registration_mode(REGISTRATION_ALLOWLIST)
+        .unwrap();
+
+    let registrar = Address::from([7u8; 20]);
+    contract
+        .grant_role(VOTER_REGISTRAR_ROLE, registrar)
+        .unwrap();
+
+    let voters: Vec<Address> = (10u8..15).map(|b| Address::from([b; 20])).collect();
+
+    vm.set_sender(voters[0]);
+    assert!(matches!(
+        contract.register_voter(identity_of(voters[0]), Vec::new().into()),
+        Err(QuadraticVotingError::Unauthorized(_))
+    ));
+    assert!(matches!(
+        contract.register_voters(voters.clone()),
+        Err(QuadraticVotingError::Unauthorized(_))
+    ));
+
+    vm.set_sender(registrar);
+    contract.register_voters(voters.clone()).unwrap();
+    // Re-registering is a no-op rather than an error
+    contract.register_voters(voters[..2].to_vec()).unwrap();
+
+    for &voter in &voters {
+        assert_eq!(contract.get_voter_status(voter), (true, false, false));
+    }
+}
+
+#[test]
+fn test_suspend_and_remove_voter() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let session_id = contract
+        .create_session(
+            "Test".to_string(),
+            "Desc".to_string(),
+            U256::from(100),
+            U64::ZERO,
+            U64::from(3600),
+            vec![("Proposal".to_string(), "Desc".to_string())],
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+
+    let voter = Address::from([2u8; 20]);
+    vm.set_sender(voter);
+    contract
+        .register_voter(identity_of(voter), Vec::new().into())
+        .unwrap();
+    contract
+        .vote(session_id, vec![U16::from(1)], vec![U64::from(2)])
+        .unwrap();
+
+    vm.set_sender(admin);
+    contract.set_voter_suspended(voter, true).unwrap();
+    let logs = vm.get_emitted_logs();
+    let (topics, data) = logs.last().unwrap();
+    assert_eq!(topics[0], VoterSuspended::SIGNATURE_HASH);
+    let event = VoterSuspended::decode_raw_log(topics.clone(), data, true).unwrap();
+    assert_eq!(event.voter, voter);
+    assert!(event.suspended);
+
+    vm.set_sender(voter);
+    assert!(matches!(
+        contract.vote(session_id, vec![U16::from(1)], vec![U64::from(1)]),
+        Err(QuadraticVotingError::Unauthorized(_))
+    ));
+
+    vm.set_sender(admin);
+    contract.set_voter_suspended(voter, false).unwrap();
+    vm.set_sender(voter);
+    contract
+        .vote(session_id, vec![U16::from(1)], vec![U64::from(1)])
+        .unwrap();
+
+    vm.set_sender(admin);
+    contract.remove_voter(voter).unwrap();
+    let logs = vm.get_emitted_logs();
+    let (topics, _) = logs.last().unwrap();
+    assert_eq!(topics[0], VoterRemoved::SIGNATURE_HASH);
+    assert_eq!(contract.get_voter_status(voter), (false, false, false));
+    assert!(contract.remove_voter(voter).is_err());
+    assert!(contract.set_voter_suspended(voter, true).is_err());
+
+    // Votes already cast stay in the tally
+    let proposals = contract.get_session_proposals(session_id, U16::ZERO, U16::MAX).unwrap();
+    assert_eq!(proposals[0].3, U64::from(1));
+
+    vm.set_sender(voter);
+    assert!(matches!(
+        contract.vote(session_id, vec![U16::from(1)], vec![U64::from(2)]),
+        Err(QuadraticVotingError::VoterNotRegistered(_))
+    ));
+
+    // In open mode a removed voter can register again
+    contract
+        .register_voter(identity_of(voter), Vec::new().into())
+        .unwrap();
+    assert_eq!(contract.get_voter_status(voter), (true, false, false));
+}
+
+#[test]
+fn test_identity_commitment_is_unique() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let voter = Address::from([2u8; 20]);
+    let identity = identity_commitment("voter@example.com", B256::repeat_byte(0x11));
+    vm.set_sender(voter);
+    assert!(matches!(
+        contract.register_voter(B256::ZERO, Vec::new().into()),
+        Err(QuadraticVotingError::InvalidCommitment(_))
+    ));
+    contract.register_voter(identity, Vec::new().into()).unwrap();
+    assert_eq!(contract.get_identity_owner(identity), voter);
+
+    // Only the commitment is published, never the email
+    let logs = vm.get_emitted_logs();
+    let (topics, data) = logs.last().unwrap();
+    let event = VoterRegistered::decode_raw_log(topics.clone(), data, true).unwrap();
+    assert_eq!(event.voter, voter);
+    assert_eq!(event.identity, identity);
+    assert!(!data.windows(5).any(|window| window == b"voter"));
+
+    // The same identity cannot back a second address, even after its owner is removed
+    let other = Address::from([3u8; 20]);
+    vm.set_sender(other);
+    assert!(matches!(
+        contract.register_voter(identity, Vec::new().into()),
+        Err(QuadraticVotingError::IdentityAlreadyRegistered(_))
+    ));
+    vm.set_sender(admin);
+    contract.remove_voter(voter).unwrap();
+    vm.set_sender(other);
+    assert!(matches!(
+        contract.register_voter(identity, Vec::new().into()),
+        Err(QuadraticVotingError::IdentityAlreadyRegistered(_))
+    ));
+
+    // A different salt gives a different commitment
+    let salted = identity_commitment("voter@example.com", B256::repeat_byte(0x22));
+    assert_ne!(salted, identity);
+    contract.register_voter(salted, Vec::new().into()).unwrap();
+    assert_eq!(contract.get_voter_status(other), (true, false, false));
+}
+
+#[test]
+fn test_registrar_attestation_admits_voter() {
+    use k256::ecdsa::SigningKey;
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+    contract
+        .set_registration_mode(REGISTRATION_ALLOWLIST)
+        .unwrap();
+
+    let key = SigningKey::from_slice(&[0x42u8; 32]).unwrap();
+    let registrar = signer::address_of(&key);
+
+    let voter = Address::from([2u8; 20]);
+    let identity = identity_of(voter);
+    let digest = registration_digest(vm.chain_id(), vm.contract_address(), voter, identity);
+    let attestation = signer::sign_digest(&key, digest);
+    mock_ecrecover(&vm, digest, &attestation);
+
+    // Signatures from accounts without VOTER_REGISTRAR_ROLE are rejected
+    vm.set_sender(voter);
+    assert!(matches!(
+        contract.register_voter(identity, attestation.to_vec().into()),
+        Err(QuadraticVotingError::InvalidSignature(_))
+    ));
+    assert!(matches!(
+        contract.register_voter(identity, attestation[..64].to_vec().into()),
+        Err(QuadraticVotingError::InvalidSignature(_))
+    ));
+    assert_eq!(contract.get_identity_owner(identity), Address::ZERO);
+
+    vm.set_sender(admin);
+    contract
+        .grant_role(VOTER_REGISTRAR_ROLE, registrar)
+        .unwrap();
+
+    // The attestation is bound to the voter, so nobody else can replay it
+    let other = Address::from([3u8; 20]);
+    vm.set_sender(other);
+    assert!(contract
+        .register_voter(identity, attestation.to_vec().into())
+        .is_err());
+    assert!(matches!(
+        contract.register_voter(identity_of(other), Vec::new().into()),
+        Err(QuadraticVotingError::Unauthorized(_))
+    ));
+
+    // An attested voter skips the allowlist
+    vm.set_sender(voter);
+    contract
+        .register_voter(identity, attestation.to_vec().into())
+        .unwrap();
+    assert_eq!(contract.get_voter_status(voter), (true, false, false));
+    assert_eq!(contract.get_identity_owner(identity), voter);
+}
+
+/// Merkle eligibility
+#[test]
+fn test_merkle_gated_session() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let eligible: Vec<(Address, U256)> = (10u8..20)
+        .map(|b| (Address::from([b; 20]), U256::from(b as u64 * 2)))
+        .collect();
+    let tree = MerkleTree::new(&eligible);
+
+    let session_id = contract
+        .create_session(
+            "Community".to_string(),
+            "Desc".to_string(),
+            U256::from(100),
+            U64::ZERO,
+            U64::from(3600),
+            vec![
+                ("Proposal 1".to_string(), "Desc 1".to_string()),
+                ("Proposal 2".to_string(), "Desc 2".to_string()),
+            ],
+            tree.root(),
+            U16::from(1),
+        )
+        .unwrap();
+
+    // Eligible voters do not need register_voter
+    let (voter, allocation) = eligible[3]; // 26 credits
+    let proof = tree.proof(3).unwrap();
+    vm.set_sender(voter);
+
+    // Plain vote fails until eligibility is proven
+    assert!(matches!(
+        contract.vote(session_id, vec![U16::from(1)], vec![U64::from(1)]),
+        Err(QuadraticVotingError::VoterNotRegistered(_))
+    ));
+    assert_eq!(
+        contract.get_voter_session_credits(session_id, voter).unwrap(),
+        (U256::ZERO, U256::ZERO, U256::ZERO)
+    );
+
+    // Claiming a larger allocation than the leaf fails
+    assert!(matches!(
+        contract.vote_with_proof(
+            session_id,
+            vec![U16::from(1)],
+            vec![U64::from(1)],
+            Vec::new(),
+            U256::from(100),
+            proof.clone(),
+        ),
+        Err(QuadraticVotingError::InvalidProof(_))
+    ));
+
+    contract
+        .vote_with_proof(
+            session_id,
+            vec![U16::from(1)],
+            vec![U64::from(4)],
+            Vec::new(),
+            allocation,
+            proof,
+        )
+        .unwrap();
+    assert_eq!(
+        contract.get_voter_session_credits(session_id, voter).unwrap(),
+        (U256::from(26), U256::from(16), U256::from(10))
+    );
+
+    // Later votes need no proof and are bounded by the leaf allocation, not credits_per_voter
+    assert!(matches!(
+        contract.vote(session_id, vec![U16::from(2)], vec![U64::from(4)]),
+        Err(QuadraticVotingError::InsufficientCredits(_))
+    ));
+    contract
+        .vote_with_proof(session_id, vec![U16::from(2)], vec![U64::from(3)], Vec::new(), U256::ZERO, vec![])
+        .unwrap();
+
+    // Someone else's proof is useless, and registration does not bypass the root
+    let outsider = Address::from([3u8; 20]);
+    vm.set_sender(outsider);
+    contract
+        .register_voter(identity_of(outsider), Vec::new().into())
+        .unwrap();
+    assert!(matches!(
+        contract.vote_with_proof(
+            session_id,
+            vec![U16::from(1)],
+            vec![U64::from(1)],
+            Vec::new(),
+            eligible[0].1,
+            tree.proof(0).unwrap(),
+        ),
+        Err(QuadraticVotingError::InvalidProof(_))
+    ));
+    assert!(matches!(
+        contract.vote(session_id, vec![U16::from(1)], vec![U64::from(1)]),
+        Err(QuadraticVotingError::VoterNotRegistered(_))
+    ));
+}
+
+#[test]
+fn test_vote_with_proof_requires_merkle_session() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let session_id = contract
+        .create_session(
+            "Test".to_string(),
+            "Desc".to_string(),
+            U256::from(100),
+            U64::ZERO,
+            U64::from(3600),
+            vec![("Proposal".to_string(), "Desc".to_string())],
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+
+    let result = contract.vote_with_proof(
+        session_id,
+        vec![U16::from(1)],
+        vec![U64::from(1)],
+        Vec::new(),
+        U256::from(100),
+        vec![],
+    );
+    assert!(matches!(
+        result,
+        Err(QuadraticVotingError::InvalidSession(InvalidSession {}))
+    ));
+}
+
+/// Weighted credits
+#[test]
+fn test_weighted_voter_credits() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let gold = Address::from([2u8; 20]);
+    let silver = Address::from([3u8; 20]);
+    let regular = Address::from([4u8; 20]);
+    for voter in [gold, silver, regular] {
+        vm.set_sender(voter);
+        contract
+            .register_voter(identity_of(voter), Vec::new().into())
+            .unwrap();
+    }
+
+    vm.set_sender(admin);
+    let session_id = contract
+        .create_session(
+            "Tiers".to_string(),
+            "Desc".to_string(),
+            U256::from(10),
+            U64::from(1_000),
+            U64::from(3600),
+            vec![("Proposal".to_string(), "Desc".to_string())],
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+
+    // Only the creator or an admin may assign budgets, with matching lengths
+    vm.set_sender(gold);
+    assert!(matches!(
+        contract.set_voter_credits(session_id, vec![gold], vec![U256::from(1_000)]),
+        Err(QuadraticVotingError::Unauthorized(_))
+    ));
+    vm.set_sender(admin);
+    assert!(matches!(
+        contract.set_voter_credits(session_id, vec![gold, silver], vec![U256::from(100)]),
+        Err(QuadraticVotingError::InvalidVoteCount(_))
+    ));
+
+    contract
+        .set_voter_credits(
+            session_id,
+            vec![gold, silver],
+            vec![U256::from(100), U256::from(25)],
+        )
+        .unwrap();
+
+    assert_eq!(
+        contract.get_voter_allocation(session_id, gold).unwrap(),
+        (U256::from(100), true)
+    );
+    assert_eq!(
+        contract.get_voter_allocation(session_id, regular).unwrap(),
+        (U256::from(10), false)
+    );
+
+    vm.set_block_timestamp(1_000);
+
+    // Budgets are frozen once the session starts
+    assert!(matches!(
+        contract.set_voter_credits(session_id, vec![regular], vec![U256::from(100)]),
+        Err(QuadraticVotingError::InvalidSession(_))
+    ));
+
+    vm.set_sender(gold);
+    contract
+        .vote(session_id, vec![U16::from(1)], vec![U64::from(10)])
+        .unwrap();
+
+    vm.set_sender(silver);
+    assert!(contract
+        .vote(session_id, vec![U16::from(1)], vec![U64::from(6)])
+        .is_err());
+    contract
+        .vote(session_id, vec![U16::from(1)], vec![U64::from(5)])
+        .unwrap();
+
+    vm.set_sender(regular);
+    assert!(contract
+        .vote(session_id, vec![U16::from(1)], vec![U64::from(4)])
+        .is_err());
+    contract
+        .vote(session_id, vec![U16::from(1)], vec![U64::from(3)])
+        .unwrap();
+
+    assert_eq!(
+        contract.get_voter_session_credits(session_id, silver).unwrap(),
+        (U256::from(25), U256::from(25), U256::ZERO)
+    );
+    let proposals = contract.get_session_proposals(session_id, U16::ZERO, U16::MAX).unwrap();
+    assert_eq!(proposals[0].3, U64::from(18));
+}
+
+/// Commit-reveal
+#[test]
+fn test_commit_reveal_flow() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let alice = Address::from([2u8; 20]);
+    let bob = Address::from([3u8; 20]);
+    for voter in [alice, bob] {
+        vm.set_sender(voter);
+        contract
+            .register_voter(identity_of(voter), Vec::new().into())
+            .unwrap();
+    }
+
+    // Commit phase [100, 200), reveal phase [200, 300)
+    vm.set_sender(admin);
+    let session_id = contract
+        .create_session(
+            "Private".to_string(),
+            "Desc".to_string(),
+            U256::from(20),
+            U64::from(100),
+            U64::from(100),
+            vec![
+                ("Proposal 1".to_string(), "Desc 1".to_string()),
+                ("Proposal 2".to_string(), "Desc 2".to_string()),
+            ],
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+    assert!(contract.enable_commit_reveal(session_id, U64::ZERO).is_err());
+    contract
+        .enable_commit_reveal(session_id, U64::from(100))
+        .unwrap();
+    assert_eq!(
+        contract.get_reveal_window(session_id).unwrap(),
+        (U256::from(200), U256::from(300))
+    );
+
+    let alice_ids = vec![U16::from(1), U16::from(2)];
+    let alice_counts = vec![U64::from(4), U64::from(1)];
+    let alice_salt = B256::from([0xaa; 32]);
+    let alice_commitment = vote_commitment(session_id, alice, &alice_ids, &alice_counts, &[], alice_salt);
+
+    vm.set_block_timestamp(150);
+    vm.set_sender(alice);
+
+    // Votes in the clear are not accepted
+    assert!(matches!(
+        contract.vote(session_id, alice_ids.clone(), alice_counts.clone()),
+        Err(QuadraticVotingError::InvalidSession(_))
+    ));
+
+    contract.commit_vote(session_id, alice_commitment).unwrap();
+    let logs = vm.get_emitted_logs();
+    let (topics, _) = logs.last().unwrap();
+    assert_eq!(topics[0], VoteCommitted::SIGNATURE_HASH);
+
+    // Nothing is visible in the tally during the commit phase
+    assert_eq!(contract.get_session_results(session_id).unwrap().3, U64::ZERO);
+
+    // Revealing is only possible after the commit phase
+    assert!(contract
+        .reveal_vote(session_id, alice_ids.clone(), alice_counts.clone(), Vec::new(), alice_salt)
+        .is_err());
+
+    vm.set_sender(bob);
+    let bob_ids = vec![U16::from(2)];
+    let bob_counts = vec![U64::from(3)];
+    contract
+        .commit_vote(
+            session_id,
+            vote_commitment(session_id, bob, &bob_ids, &bob_counts, &[], B256::from([0xbb; 32])),
+        )
+        .unwrap();
+
+    vm.set_block_timestamp(200);
+    assert!(matches!(
+        contract.commit_vote(session_id, alice_commitment),
+        Err(QuadraticVotingError::InvalidSession(_))
+    ));
+
+    // Extending during the reveal phase would reopen commits to voters who saw revealed ballots
+    vm.set_sender(admin);
+    assert!(matches!(
+        contract.extend_session(session_id, U64::from(400)),
+        Err(QuadraticVotingError::InvalidSession(_))
+    ));
+
+    // A reveal must match the commitment exactly, and another voter cannot reuse it
+    vm.set_sender(alice);
+    assert!(matches!(
+        contract.reveal_vote(session_id, alice_ids.clone(), vec![U64::from(4), U64::from(2)], Vec::new(), alice_salt),
+        Err(QuadraticVotingError::InvalidCommitment(_))
+    ));
+    assert!(matches!(
+        contract.reveal_vote(session_id, alice_ids.clone(), alice_counts.clone(), Vec::new(), B256::ZERO),
+        Err(QuadraticVotingError::InvalidCommitment(_))
+    ));
+    vm.set_sender(bob);
+    assert!(matches!(
+        contract.reveal_vote(session_id, alice_ids.clone(), alice_counts.clone(), Vec::new(), alice_salt),
+        Err(QuadraticVotingError::InvalidCommitment(_))
+    ));
+
+    vm.set_sender(alice);
+    contract
+        .reveal_vote(session_id, alice_ids.clone(), alice_counts.clone(), Vec::new(), alice_salt)
+        .unwrap();
+    let logs = vm.get_emitted_logs();
+    let (topics, _) = logs.last().unwrap();
+    assert_eq!(topics[0], VoteRevealed::SIGNATURE_HASH);
+    assert!(matches!(
+        contract.reveal_vote(session_id, alice_ids, alice_counts, Vec::new(), alice_salt),
+        Err(QuadraticVotingError::InvalidCommitment(_))
+    ));
+
+    // Bob never reveals, so only Alice's ballot counts
+    vm.set_block_timestamp(300);
+    let proposals = contract.get_session_proposals(session_id, U16::ZERO, U16::MAX).unwrap();
+    assert_eq!(proposals[0].3, U64::from(4));
+    assert_eq!(proposals[1].3, U64::from(1));
+    assert_eq!(
+        contract.get_voter_session_credits(session_id, alice).unwrap(),
+        (U256::from(20), U256::from(17), U256::from(3))
+    );
+}
+
+#[test]
+fn test_reveal_applies_credit_check() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let voter = Address::from([2u8; 20]);
+    vm.set_sender(voter);
+    contract
+        .register_voter(identity_of(voter), Vec::new().into())
+        .unwrap();
+
+    vm.set_sender(admin);
+    let session_id = contract
+        .create_session(
+            "Private".to_string(),
+            "Desc".to_string(),
+            U256::from(10),
+            U64::from(100),
+            U64::from(100),
+            vec![("Proposal".to_string(), "Desc".to_string())],
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+    contract
+        .enable_commit_reveal(session_id, U64::from(100))
+        .unwrap();
+
+    // Commitments hide the cost, so an over-budget ballot is only caught at reveal
+    let ids = vec![U16::from(1)];
+    let counts = vec![U64::from(4)];
+    let salt = B256::from([7u8; 32]);
+    vm.set_block_timestamp(100);
+    vm.set_sender(voter);
+    contract
+        .commit_vote(session_id, vote_commitment(session_id, voter, &ids, &counts, &[], salt))
+        .unwrap();
+
+    vm.set_block_timestamp(250);
+    assert!(matches!(
+        contract.reveal_vote(session_id, ids, counts, Vec::new(), salt),
+        Err(QuadraticVotingError::InsufficientCredits(_))
+    ));
+    assert_eq!(contract.get_session_results(session_id).unwrap().3, U64::ZERO);
+}
+
+#[test]
+fn test_reveal_requires_undelegating() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let voter = Address::from([2u8; 20]);
+    let delegate = Address::from([3u8; 20]);
+    for account in [voter, delegate] {
+        vm.set_sender(account);
+        contract
+            .register_voter(identity_of(account), Vec::new().into())
+            .unwrap();
+    }
+
+    vm.set_sender(admin);
+    let session_id = contract
+        .create_session(
+            "Private".to_string(),
+            "Desc".to_string(),
+            U256::from(10),
+            U64::from(100),
+            U64::from(100),
+            vec![("Proposal".to_string(), "Desc".to_string())],
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+    contract
+        .enable_commit_reveal(session_id, U64::from(100))
+        .unwrap();
+
+    let ids = vec![U16::from(1)];
+    let counts = vec![U64::from(3)];
+    let salt = B256::from([7u8; 32]);
+    let commitment = vote_commitment(session_id, voter, &ids, &counts, &[], salt);
+    vm.set_block_timestamp(100);
+    vm.set_sender(voter);
+    contract.commit_vote(session_id, commitment).unwrap();
+
+    // Once the credits are handed to a delegate, the voter can neither commit nor reveal
+    contract.delegate(session_id, delegate).unwrap();
+    assert!(matches!(
+        contract.commit_vote(session_id, commitment),
+        Err(QuadraticVotingError::Unauthorized(_))
+    ));
+    vm.set_block_timestamp(250);
+    assert!(matches!(
+        contract.reveal_vote(session_id, ids.clone(), counts.clone(), Vec::new(), salt),
+        Err(QuadraticVotingError::Unauthorized(_))
+    ));
+
+    contract.undelegate(session_id).unwrap();
+    contract
+        .reveal_vote(session_id, ids, counts, Vec::new(), salt)
+        .unwrap();
+    assert_eq!(contract.get_session_results(session_id).unwrap().3, U64::from(3));
+}
+
+#[test]
+fn test_commit_reveal_rejects_merkle_sessions() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let eligible = vec![(Address::from([10u8; 20]), U256::from(20))];
+    let tree = MerkleTree::new(&eligible);
+    let session_id = contract
+        .create_session(
+            "Private".to_string(),
+            "Desc".to_string(),
+            U256::from(20),
+            U64::from(100),
+            U64::from(100),
+            vec![("Proposal".to_string(), "Desc".to_string())],
+            tree.root(),
+            U16::from(1),
+        )
+        .unwrap();
+
+    // Listed voters prove eligibility by voting in the clear, so they could never commit
+    assert!(matches!(
+        contract.enable_commit_reveal(session_id, U64::from(100)),
+        Err(QuadraticVotingError::InvalidSession(_))
+    ));
+    assert_eq!(
+        contract.get_reveal_window(session_id).unwrap(),
+        (U256::ZERO, U256::ZERO)
+    );
+
+    // The session keeps voting in the clear with proofs
+    vm.set_block_timestamp(100);
+    vm.set_sender(eligible[0].0);
+    contract
+        .vote_with_proof(
+            session_id,
+            vec![U16::from(1)],
+            vec![U64::from(4)],
+            Vec::new(),
+            eligible[0].1,
+            tree.proof(0).unwrap(),
+        )
+        .unwrap();
+}
+
+/// Delegation
+#[test]
+fn test_delegate_spends_delegator_budget() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let delegator = Address::from([2u8; 20]);
+    let delegate = Address::from([3u8; 20]);
+    for voter in [delegator, delegate] {
+        vm.set_sender(voter);
+        contract
+            .register_voter(identity_of(voter), Vec::new().into())
+            .unwrap();
+    }
+
+    vm.set_sender(admin);
+    let session_id = contract
+        .create_session(
+            "Test".to_string(),
+            "Desc".to_string(),
+            U256::from(10),
+            U64::ZERO,
+            U64::from(3600),
+            vec![("Proposal".to_string(), "Desc".to_string())],
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+
+    // Not yet delegated
+    vm.set_sender(delegate);
+    assert!(matches!(
+        contract.vote_as_delegate(session_id, delegator, vec![U16::from(1)], vec![U64::from(1)], Vec::new()),
+        Err(QuadraticVotingError::Unauthorized(_))
+    ));
+
+    vm.set_sender(delegator);
+    contract.delegate(session_id, delegate).unwrap();
+    let logs = vm.get_emitted_logs();
+    let (topics, data) = logs.last().unwrap();
+    assert_eq!(topics[0], DelegateChanged::SIGNATURE_HASH);
+    let event = DelegateChanged::decode_raw_log(topics.clone(), data, true).unwrap();
+    assert_eq!(event.delegator, delegator);
+    assert_eq!(event.from_delegate, Address::ZERO);
+    assert_eq!(event.to_delegate, delegate);
+
+    // The delegator cannot vote while delegated
+    assert!(matches!(
+        contract.vote(session_id, vec![U16::from(1)], vec![U64::from(1)]),
+        Err(QuadraticVotingError::Unauthorized(_))
+    ));
+
+    // Budgets stay separate: 3 votes each costs 9 + 9, while 4 votes
+    // from the delegator's 10 credits is still too expensive
+    vm.set_sender(delegate);
+    contract
+        .vote(session_id, vec![U16::from(1)], vec![U64::from(3)])
+        .unwrap();
+    assert!(matches!(
+        contract.vote_as_delegate(session_id, delegator, vec![U16::from(1)], vec![U64::from(4)], Vec::new()),
+        Err(QuadraticVotingError::InsufficientCredits(_))
+    ));
+    contract
+        .vote_as_delegate(session_id, delegator, vec![U16::from(1)], vec![U64::from(3)], Vec::new())
+        .unwrap();
+
+    let proposals = contract.get_session_proposals(session_id, U16::ZERO, U16::MAX).unwrap();
+    assert_eq!(proposals[0].3, U64::from(6));
+    assert_eq!(
+        contract.get_voter_session_credits(session_id, delegator).unwrap().1,
+        U256::from(9)
+    );
+
+    // A suspended delegate cannot keep spending the delegated credits
+    vm.set_sender(admin);
+    contract.set_voter_suspended(delegate, true).unwrap();
+    vm.set_sender(delegate);
+    assert!(matches!(
+        contract.vote_as_delegate(session_id, delegator, vec![U16::from(1)], vec![U64::from(2)], Vec::new()),
+        Err(QuadraticVotingError::Unauthorized(_))
+    ));
+    vm.set_sender(admin);
+    contract.set_voter_suspended(delegate, false).unwrap();
+
+    // After undelegating the delegator votes again and the delegate loses access
+    vm.set_sender(delegator);
+    contract.undelegate(session_id).unwrap();
+    assert!(contract.undelegate(session_id).is_err());
+    contract
+        .vote(session_id, vec![U16::from(1)], vec![U64::from(1)])
+        .unwrap();
+
+    vm.set_sender(delegate);
+    assert!(matches!(
+        contract.vote_as_delegate(session_id, delegator, vec![U16::from(1)], vec![U64::from(2)], Vec::new()),
+        Err(QuadraticVotingError::Unauthorized(_))
+    ));
+}
+
+#[test]
+fn test_delegation_chains_and_cycles() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let voters: Vec<Address> = (10u8..22).map(|b| Address::from([b; 20])).collect();
+    for &voter in &voters {
+        vm.set_sender(voter);
+        contract
+            .register_voter(identity_of(voter), Vec::new().into())
+            .unwrap();
+    }
+
+    vm.set_sender(admin);
+    let session_id = contract
+        .create_session(
+            "Test".to_string(),
+            "Desc".to_string(),
+            U256::from(10),
+            U64::ZERO,
+            U64::from(3600),
+            vec![("Proposal".to_string(), "Desc".to_string())],
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+
+    let (a, b, c) = (voters[0], voters[1], voters[2]);
+
+    vm.set_sender(a);
+    assert!(matches!(
+        contract.delegate(session_id, a),
+        Err(QuadraticVotingError::DelegationCycle(_))
+    ));
+    assert!(matches!(
+        contract.delegate(session_id, Address::ZERO),
+        Err(QuadraticVotingError::InvalidDelegate(_))
+    ));
+
+    // a -> b -> c: only c, the end of the chain, votes for a
+    contract.delegate(session_id, b).unwrap();
+    vm.set_sender(b);
+    contract.delegate(session_id, c).unwrap();
+    assert_eq!(contract.get_delegate(session_id, a), (b, c));
+
+    assert!(matches!(
+        contract.vote_as_delegate(session_id, a, vec![U16::from(1)], vec![U64::from(1)], Vec::new()),
+        Err(QuadraticVotingError::Unauthorized(_))
+    ));
+    vm.set_sender(c);
+    contract
+        .vote_as_delegate(session_id, a, vec![U16::from(1)], vec![U64::from(1)], Vec::new())
+        .unwrap();
+    contract
+        .vote_as_delegate(session_id, b, vec![U16::from(1)], vec![U64::from(1)], Vec::new())
+        .unwrap();
+
+    // c -> a would close the loop
+    assert!(matches!(
+        contract.delegate(session_id, a),
+        Err(QuadraticVotingError::DelegationCycle(_))
+    ));
+
+    // Chains are capped in length
+    for i in 3..voters.len() - 1 {
+        vm.set_sender(voters[i]);
+        contract.delegate(session_id, voters[i + 1]).unwrap();
+    }
+    vm.set_sender(c);
+    assert!(matches!(
+        contract.delegate(session_id, voters[3]),
+        Err(QuadraticVotingError::InvalidDelegate(_))
+    ));
+}
+
+#[test]
+fn test_delegation_depth_counts_upstream_links() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let voters: Vec<Address> = (10u8..22).map(|b| Address::from([b; 20])).collect();
+    for &voter in &voters {
+        vm.set_sender(voter);
+        contract
+            .register_voter(identity_of(voter), Vec::new().into())
+            .unwrap();
+    }
+
+    vm.set_sender(admin);
+    let session_id = contract
+        .create_session(
+            "Test".to_string(),
+            "Desc".to_string(),
+            U256::from(10),
+            U64::ZERO,
+            U64::from(3600),
+            vec![("Proposal".to_string(), "Desc".to_string())],
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+
+    // Growing the chain at its end counts the links leading up to it: v0 -> ... -> v8 is the
+    // longest allowed, so v8 -> v9 would strand v0's credits
+    for i in 0..8 {
+        vm.set_sender(voters[i]);
+        contract.delegate(session_id, voters[i + 1]).unwrap();
+    }
+    vm.set_sender(voters[8]);
+    assert!(matches!(
+        contract.delegate(session_id, voters[9]),
+        Err(QuadraticVotingError::InvalidDelegate(_))
+    ));
+    assert_eq!(contract.get_delegate(session_id, voters[0]), (voters[1], voters[8]));
+    contract
+        .vote_as_delegate(session_id, voters[0], vec![U16::from(1)], vec![U64::from(1)], Vec::new())
+        .unwrap();
+
+    // Moving the head of the chain elsewhere frees up room at its end
+    vm.set_sender(voters[0]);
+    contract.delegate(session_id, voters[10]).unwrap();
+    vm.set_sender(voters[8]);
+    contract.delegate(session_id, voters[9]).unwrap();
+    assert_eq!(contract.get_delegate(session_id, voters[1]), (voters[2], voters[9]));
+
+    // v1 -> ... -> v9 is full again, so neither end can grow
+    vm.set_sender(voters[0]);
+    assert!(matches!(
+        contract.delegate(session_id, voters[1]),
+        Err(QuadraticVotingError::InvalidDelegate(_))
+    ));
+    vm.set_sender(voters[9]);
+    assert!(matches!(
+        contract.delegate(session_id, voters[11]),
+        Err(QuadraticVotingError::InvalidDelegate(_))
+    ));
+
+    // Undelegating shortens the chain for everyone upstream
+    vm.set_sender(voters[1]);
+    contract.undelegate(session_id).unwrap();
+    vm.set_sender(voters[9]);
+    contract.delegate(session_id, voters[11]).unwrap();
+    assert_eq!(contract.get_delegate(session_id, voters[2]), (voters[3], voters[11]));
+}
+
+/// Signed ballots
+// TestVM has no precompiles, so ecrecover is mocked with the address k256 recovers
+fn mock_ecrecover(vm: &stylus_sdk::testing::TestVM, digest: B256, signature: &[u8; 65]) {
+    use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+    let key = VerifyingKey::recover_from_prehash(
+        digest.as_slice(),
+        &Signature::from_slice(&signature[..64]).unwrap(),
+        RecoveryId::from_byte(signature[64] - 27).unwrap(),
+    )
+    .unwrap();
+    let point = key.to_encoded_point(false);
+    let mut output = vec![0u8; 12];
+    output.extend_from_slice(&keccak256(&point.as_bytes()[1..])[12..]);
+
+    let mut input = digest.to_vec();
+    input.extend_from_slice(&[0u8; 31]);
+    input.push(signature[64]);
+    input.extend_from_slice(&signature[..64]);
+    vm.mock_static_call(Address::with_last_byte(1), input, Ok(output));
+}
+
+#[test]
+fn test_vote_by_sig_relays_signed_ballot() {
+    use k256::ecdsa::SigningKey;
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let key = SigningKey::from_slice(&[0x42u8; 32]).unwrap();
+    let voter = signer::address_of(&key);
+    vm.set_sender(voter);
+    contract
+        .register_voter(identity_of(voter), Vec::new().into())
+        .unwrap();
+
+    vm.set_sender(admin);
+    let session_id = contract
+        .create_session(
+            "Test".to_string(),
+            "Desc".to_string(),
+            U256::from(100),
+            U64::ZERO,
+            U64::from(3600),
+            vec![
+                ("Proposal 1".to_string(), "Desc".to_string()),
+                ("Proposal 2".to_string(), "Desc".to_string()),
+            ],
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+
+    let ids = vec![U16::from(1), U16::from(2)];
+    let counts = vec![U64::from(3), U64::from(4)];
+    let deadline = U256::from(vm.block_timestamp() + 600);
+    let digest = ballot_digest(
+        vm.chain_id(),
+        vm.contract_address(),
+        session_id,
+        &ids,
+        &counts,
+        &[],
+        voter,
+        U256::ZERO,
+        deadline,
+    );
+    let signature = signer::sign_digest(&key, digest);
+    mock_ecrecover(&vm, digest, &signature);
+
+    // Anyone can relay the ballot
+    let relayer = Address::from([9u8; 20]);
+    vm.set_sender(relayer);
+    assert_eq!(contract.get_nonce(voter), U256::ZERO);
+    contract
+        .vote_by_sig(
+            session_id,
+            ids.clone(),
+            counts.clone(),
+            Vec::new(),
+            voter,
+            U256::ZERO,
+            deadline,
+            signature.to_vec().into(),
+        )
+        .unwrap();
+
+    // Votes and credits are booked to the signer, not the relayer
+    let (_, spent, remaining) = contract
+        .get_voter_session_credits(session_id, voter)
+        .unwrap();
+    assert_eq!(spent, U256::from(25));
+    assert_eq!(remaining, U256::from(75));
+    assert_eq!(contract.get_nonce(voter), U256::from(1));
+
+    // The same signature cannot be replayed
+    assert!(matches!(
+        contract.vote_by_sig(
+            session_id,
+            ids,
+            counts,
+            Vec::new(),
+            voter,
+            U256::ZERO,
+            deadline,
+            signature.to_vec().into(),
+        ),
+        Err(QuadraticVotingError::InvalidNonce(_))
+    ));
+}
+
+#[test]
+fn test_vote_by_sig_rejects_bad_signatures() {
+    use k256::ecdsa::SigningKey;
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let key = SigningKey::from_slice(&[0x42u8; 32]).unwrap();
+    let voter = signer::address_of(&key);
+    vm.set_sender(voter);
+    contract
+        .register_voter(identity_of(voter), Vec::new().into())
+        .unwrap();
+
+    vm.set_sender(admin);
+    let session_id = contract
+        .create_session(
+            "Test".to_string(),
+            "Desc".to_string(),
+            U256::from(100),
+            U64::ZERO,
+            U64::from(3600),
+            vec![("Proposal".to_string(), "Desc".to_string())],
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+
+    let ids = vec![U16::from(1)];
+    let counts = vec![U64::from(2)];
+    let now = vm.block_timestamp();
+    let digest_for = |nonce: U256, deadline: U256| {
+        ballot_digest(
+            vm.chain_id(),
+            vm.contract_address(),
+            session_id,
+            &ids,
+            &counts,
+            &[],
+            voter,
+            nonce,
+            deadline,
+        )
+    };
+
+    // Signed by someone other than the voter
+    let deadline = U256::from(now + 600);
+    let digest = digest_for(U256::ZERO, deadline);
+    let impostor = SigningKey::from_slice(&[0x24u8; 32]).unwrap();
+    let forged = signer::sign_digest(&impostor, digest);
+    mock_ecrecover(&vm, digest, &forged);
+    assert!(matches!(
+        contract.vote_by_sig(
+            session_id,
+            ids.clone(),
+            counts.clone(),
+            Vec::new(),
+            voter,
+            U256::ZERO,
+            deadline,
+            forged.to_vec().into(),
+        ),
+        Err(QuadraticVotingError::InvalidSignature(_))
+    ));
+
+    // Malformed and unrecoverable signatures
+    let signature = signer::sign_digest(&key, digest);
+    assert!(matches!(
+        contract.vote_by_sig(
+            session_id,
+            ids.clone(),
+            counts.clone(),
+            Vec::new(),
+            voter,
+            U256::ZERO,
+            deadline,
+            signature[..64].to_vec().into(),
+        ),
+        Err(QuadraticVotingError::InvalidSignature(_))
+    ));
+    assert!(matches!(
+        contract.vote_by_sig(
+            session_id,
+            ids.clone(),
+            counts.clone(),
+            Vec::new(),
+            voter,
+            U256::ZERO,
+            deadline,
+            signature.to_vec().into(),
+        ),
+        Err(QuadraticVotingError::InvalidSignature(_))
+    ));
+
+    // Nonces must be used in order
+    assert!(matches!(
+        contract.vote_by_sig(
+            session_id,
+            ids.clone(),
+            counts.clone(),
+            Vec::new(),
+            voter,
+            U256::from(1),
+            deadline,
+            signature.to_vec().into(),
+        ),
+        Err(QuadraticVotingError::InvalidNonce(_))
+    ));
+
+    // Expired ballots are rejected even with a valid signature
+    let expired = U256::from(now + 10);
+    let digest = digest_for(U256::ZERO, expired);
+    let signature = signer::sign_digest(&key, digest);
+    mock_ecrecover(&vm, digest, &signature);
+    vm.set_block_timestamp(now + 11);
+    assert!(matches!(
+        contract.vote_by_sig(
+            session_id,
+            ids.clone(),
+            counts.clone(),
+            Vec::new(),
+            voter,
+            U256::ZERO,
+            expired,
+            signature.to_vec().into(),
+        ),
+        Err(QuadraticVotingError::SignatureExpired(_))
+    ));
+
+    // Validation is shared with vote: a delegated voter cannot vote by signature
+    vm.set_block_timestamp(now);
+    let delegate = Address::from([3u8; 20]);
+    vm.set_sender(delegate);
+    contract
+        .register_voter(identity_of(delegate), Vec::new().into())
+        .unwrap();
+    vm.set_sender(voter);
+    contract.delegate(session_id, delegate).unwrap();
+    assert!(matches!(
+        contract.vote_by_sig(
+            session_id,
+            ids.clone(),
+            counts.clone(),
+            Vec::new(),
+            voter,
+            U256::ZERO,
+            expired,
+            signature.to_vec().into(),
+        ),
+        Err(QuadraticVotingError::Unauthorized(_))
+    ));
+    assert_eq!(contract.get_nonce(voter), U256::ZERO);
+}
+
+#[test]
+fn test_submit_ballots_skips_invalid_ballots() {
+    use k256::ecdsa::SigningKey;
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let alice_key = SigningKey::from_slice(&[0x42u8; 32]).unwrap();
+    let bob_key = SigningKey::from_slice(&[0x43u8; 32]).unwrap();
+    let alice = signer::address_of(&alice_key);
+    let bob = signer::address_of(&bob_key);
+    for voter in [alice, bob] {
+        vm.set_sender(voter);
+        contract
+            .register_voter(identity_of(voter), Vec::new().into())
+            .unwrap();
+    }
+
+    vm.set_sender(admin);
+    let session_id = contract
+        .create_session(
+            "Test".to_string(),
+            "Desc".to_string(),
+            U256::from(20),
+            U64::ZERO,
+            U64::from(3600),
+            vec![
+                ("Proposal 1".to_string(), "Desc".to_string()),
+                ("Proposal 2".to_string(), "Desc".to_string()),
+            ],
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+
+    let deadline = U256::from(vm.block_timestamp() + 600);
+    let sign = |key: &SigningKey, voter: Address, ids: Vec<U16>, counts: Vec<U64>, nonce: u64| {
+        let digest = ballot_digest(
+            vm.chain_id(),
+            vm.contract_address(),
+            session_id,
+            &ids,
+            &counts,
+            &[],
+            voter,
+            U256::from(nonce),
+            deadline,
+        );
+        let signature = signer::sign_digest(key, digest);
+        mock_ecrecover(&vm, digest, &signature);
+        (session_id, ids, counts, Vec::new(), voter, U256::from(nonce), deadline, signature.to_vec().into())
+    };
+
+    let ballots = vec![
+        // Alice votes, then raises her vote with her next nonce
+        sign(&alice_key, alice, vec![U16::from(1)], vec![U64::from(2)], 0),
+        sign(&alice_key, alice, vec![U16::from(1)], vec![U64::from(3)], 1),
+        // Bob's ballot signed by Alice's key
+        sign(&alice_key, bob, vec![U16::from(2)], vec![U64::from(1)], 0),
+        // Bob overspends: 25 > 20 credits
+        sign(&bob_key, bob, vec![U16::from(2)], vec![U64::from(5)], 0),
+        // Bob's valid ballot still goes through after his rejected ones
+        sign(&bob_key, bob, vec![U16::from(2)], vec![U64::from(4)], 0),
+    ];
+
+    let relayer = Address::from([9u8; 20]);
+    vm.set_sender(relayer);
+    let statuses = contract.submit_ballots(ballots);
+    assert_eq!(statuses, vec![true, true, false, false, true]);
+
+    let rejections: Vec<_> = vm
+        .get_emitted_logs()
+        .iter()
+        .filter(|(topics, _)| topics[0] == BallotRejected::SIGNATURE_HASH)
+        .map(|(topics, data)| BallotRejected::decode_raw_log(topics.clone(), data, true).unwrap())
+        .collect();
+    assert_eq!(rejections.len(), 2);
+    assert_eq!(rejections[0].voter, bob);
+    assert_eq!(rejections[0].reason, InvalidSignature::SELECTOR);
+    assert_eq!(rejections[1].voter, bob);
+    assert_eq!(rejections[1].reason, InsufficientCredits::SELECTOR);
+
+    assert_eq!(contract.get_nonce(alice), U256::from(2));
+    assert_eq!(contract.get_nonce(bob), U256::from(1));
+    let (_, alice_spent, _) = contract
+        .get_voter_session_credits(session_id, alice)
+        .unwrap();
+    let (_, bob_spent, _) = contract
+        .get_voter_session_credits(session_id, bob)
+        .unwrap();
+    assert_eq!(alice_spent, U256::from(9));
+    assert_eq!(bob_spent, U256::from(16));
+
+    let proposals = contract.get_session_proposals(session_id, U16::ZERO, U16::MAX).unwrap();
+    assert_eq!(proposals[0].3, U64::from(3));
+    assert_eq!(proposals[1].3, U64::from(4));
+}
+
+/// Token-backed credits
+#[test]
+fn test_token_backed_credits() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let voter = Address::from([2u8; 20]);
+    vm.set_sender(voter);
+    contract
+        .register_voter(identity_of(voter), Vec::new().into())
+        .unwrap();
+
+    let now = vm.block_timestamp();
+    vm.set_sender(admin);
+    let session_id = contract
+        .create_session(
+            "Test".to_string(),
+            "Desc".to_string(),
+            U256::from(100),
+            U64::from(now + 10),
+            U64::from(3600),
+            vec![("Proposal".to_string(), "Desc".to_string())],
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+
+    // Mock ERC-20: accepts the deposit and the refund
+    let token = Address::from([0xAAu8; 20]);
+    let amount = U256::from(50);
+    vm.mock_call(
+        token,
+        IERC20::transferFromCall { from: voter, to: vm.contract_address(), amount }.abi_encode(),
+        Ok(true.abi_encode()),
+    );
+    vm.mock_call(
+        token,
+        IERC20::transferCall { to: voter, amount }.abi_encode(),
+        Ok(true.abi_encode()),
+    );
+    contract.set_credit_token(session_id, token).unwrap();
+
+    // Without a deposit the voter has no budget, whatever credits_per_voter says
+    let (allocated, _, _) = contract
+        .get_voter_session_credits(session_id, voter)
+        .unwrap();
+    assert_eq!(allocated, U256::ZERO);
+
+    vm.set_sender(voter);
+    contract.deposit_credits(session_id, amount).unwrap();
+    let logs = vm.get_emitted_logs();
+    let (topics, data) = logs.last().unwrap();
+    assert_eq!(topics[0], CreditsDeposited::SIGNATURE_HASH);
+    let event = CreditsDeposited::decode_raw_log(topics.clone(), data, true).unwrap();
+    assert_eq!(event.voter, voter);
+    assert_eq!(event.amount, amount);
+    assert_eq!(
+        contract.get_credit_deposit(session_id, voter).unwrap(),
+        (token, amount)
+    );
+
+    // The deposit is the budget: 8 votes cost 64 > 50, 7 votes cost 49
+    vm.set_block_timestamp(now + 10);
+    assert!(matches!(
+        contract.vote(session_id, vec![U16::from(1)], vec![U64::from(8)]),
+        Err(QuadraticVotingError::InsufficientCredits(_))
+    ));
+    contract
+        .vote(session_id, vec![U16::from(1)], vec![U64::from(7)])
+        .unwrap();
+
+    // Tokens stay locked until end_time
+    assert!(matches!(
+        contract.withdraw_credits(session_id),
+        Err(QuadraticVotingError::InvalidSession(_))
+    ));
+
+    vm.set_block_timestamp(now + 10 + 3600);
+    assert_eq!(contract.withdraw_credits(session_id).unwrap(), amount);
+    let logs = vm.get_emitted_logs();
+    let (topics, data) = logs.last().unwrap();
+    assert_eq!(topics[0], CreditsWithdrawn::SIGNATURE_HASH);
+    let event = CreditsWithdrawn::decode_raw_log(topics.clone(), data, true).unwrap();
+    assert_eq!(event.amount, amount);
+    assert_eq!(
+        contract.get_credit_deposit(session_id, voter).unwrap(),
+        (token, U256::ZERO)
+    );
+
+    // Nothing left to withdraw, and the cast votes still count
+    assert!(matches!(
+        contract.withdraw_credits(session_id),
+        Err(QuadraticVotingError::InsufficientCredits(_))
+    ));
+    let (_, _, max_votes, _, _) = contract.get_session_results(session_id).unwrap();
+    assert_eq!(max_votes, U64::from(7));
+
+    // The window cannot be reopened once deposits have unlocked
+    vm.set_sender(admin);
+    assert!(matches!(
+        contract.extend_session(session_id, U64::from(now + 10_000)),
+        Err(QuadraticVotingError::InvalidSession(_))
+    ));
+}
+
+#[test]
+fn test_token_calls_use_exact_erc20_calldata() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let voter = Address::from([2u8; 20]);
+    vm.set_sender(voter);
+    contract
+        .register_voter(identity_of(voter), Vec::new().into())
+        .unwrap();
+
+    let now = vm.block_timestamp();
+    vm.set_sender(admin);
+    let session_id = contract
+        .create_session(
+            "Test".to_string(),
+            "Desc".to_string(),
+            U256::from(100),
+            U64::from(now + 10),
+            U64::from(3600),
+            vec![("Proposal".to_string(), "Desc".to_string())],
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+    let token = Address::from([0xAAu8; 20]);
+    contract.set_credit_token(session_id, token).unwrap();
+
+    // Calldata built by hand from the ERC-20 selectors and 32-byte argument words. TestVM only
+    // answers calls whose calldata matches a mock byte for byte, so any other encoding fails
+    let word = |bytes: &[u8]| {
+        let mut word = [0u8; 32];
+        word[32 - bytes.len()..].copy_from_slice(bytes);
+        word
+    };
+    let amount = word(&[0x2a]);
+    let transfer_from = [
+        &[0x23, 0xb8, 0x72, 0xdd][..],
+        &word(voter.as_slice()),
+        &word(vm.contract_address().as_slice()),
+        &amount,
+    ]
+    .concat();
+    let transfer = [&[0xa9, 0x05, 0x9c, 0xbb][..], &word(voter.as_slice()), &amount].concat();
+    let success = word(&[1]).to_vec();
+
+    // A mock for a different amount does not answer the deposit
+    vm.mock_call(
+        token,
+        IERC20::transferFromCall { from: voter, to: vm.contract_address(), amount: U256::from(43) }.abi_encode(),
+        Ok(success.clone()),
+    );
+    vm.set_sender(voter);
+    assert!(matches!(
+        contract.deposit_credits(session_id, U256::from(42)),
+        Err(QuadraticVotingError::TokenTransferFailed(_))
+    ));
+
+    vm.mock_call(token, transfer_from, Ok(success.clone()));
+    vm.mock_call(token, transfer, Ok(success));
+    contract.deposit_credits(session_id, U256::from(42)).unwrap();
+    vm.set_block_timestamp(now + 10 + 3600);
+    contract.withdraw_credits(session_id).unwrap();
+    assert_eq!(
+        contract.get_credit_deposit(session_id, voter).unwrap(),
+        (token, U256::ZERO)
+    );
+}
+
+#[test]
+fn test_token_deposit_requires_successful_transfer() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let voter = Address::from([2u8; 20]);
+    vm.set_sender(voter);
+    contract
+        .register_voter(identity_of(voter), Vec::new().into())
+        .unwrap();
+
+    let now = vm.block_timestamp();
+    vm.set_sender(admin);
+    let session_id = contract
+        .create_session(
+            "Test".to_string(),
+            "Desc".to_string(),
+            U256::from(100),
+            U64::from(now + 10),
+            U64::from(3600),
+            vec![("Proposal".to_string(), "Desc".to_string())],
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+
+    // Deposits need a token-backed session
+    vm.set_sender(voter);
+    assert!(matches!(
+        contract.deposit_credits(session_id, U256::from(10)),
+        Err(QuadraticVotingError::InvalidSession(_))
+    ));
+
+    let token = Address::from([0xAAu8; 20]);
+    vm.set_sender(voter);
+    assert!(matches!(
+        contract.set_credit_token(session_id, token),
+        Err(QuadraticVotingError::Unauthorized(_))
+    ));
+    vm.set_sender(admin);
+    contract.set_credit_token(session_id, token).unwrap();
+
+    // Custom allocations do not apply to token-backed sessions
+    assert!(matches!(
+        contract.set_voter_credits(session_id, vec![voter], vec![U256::from(500)]),
+        Err(QuadraticVotingError::InvalidSession(_))
+    ));
+
+    let deposit = |amount: u64| {
+        IERC20::transferFromCall {
+            from: voter,
+            to: vm.contract_address(),
+            amount: U256::from(amount),
+        }
+        .abi_encode()
+    };
+    // No allowance: the token reverts
+    vm.mock_call(token, deposit(10), Err(b"ERC20: insufficient allowance".to_vec()));
+    // A token returning false
+    vm.mock_call(token, deposit(20), Ok(false.abi_encode()));
+
+    vm.set_sender(voter);
+    assert!(matches!(
+        contract.deposit_credits(session_id, U256::from(10)),
+        Err(QuadraticVotingError::TokenTransferFailed(_))
+    ));
+    assert!(matches!(
+        contract.deposit_credits(session_id, U256::from(20)),
+        Err(QuadraticVotingError::TokenTransferFailed(_))
+    ));
+    // Calls to an address without code return no data
+    assert!(matches!(
+        contract.deposit_credits(session_id, U256::from(30)),
+        Err(QuadraticVotingError::TokenTransferFailed(_))
+    ));
+    assert_eq!(
+        contract.get_credit_deposit(session_id, voter).unwrap(),
+        (token, U256::ZERO)
+    );
+
+    // Unregistered accounts cannot deposit
+    vm.set_sender(Address::from([3u8; 20]));
+    assert!(matches!(
+        contract.deposit_credits(session_id, U256::from(10)),
+        Err(QuadraticVotingError::VoterNotRegistered(_))
+    ));
+}
+
+/// Quadratic funding
+#[test]
+fn test_quadratic_funding_round() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let alice = Address::from([2u8; 20]);
+    let bob = Address::from([3u8; 20]);
+    let carol = Address::from([4u8; 20]);
+    for voter in [alice, bob, carol] {
+        vm.set_sender(voter);
+        contract
+            .register_voter(identity_of(voter), Vec::new().into())
+            .unwrap();
+    }
+
+    let now = vm.block_timestamp();
+    vm.set_sender(admin);
+    let session_id = contract
+        .create_session(
+            "Grants".to_string(),
+            "Round 1".to_string(),
+            U256::from(100),
+            U64::from(now + 10),
+            U64::from(3600),
+            vec![
+                ("Project 1".to_string(), "Desc".to_string()),
+                ("Project 2".to_string(), "Desc".to_string()),
+                ("Project 3".to_string(), "Desc".to_string()),
+            ],
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+
+    let recipients = [
+        Address::from([0x11u8; 20]),
+        Address::from([0x12u8; 20]),
+        Address::from([0x13u8; 20]),
+    ];
+    assert!(matches!(
+        contract.enable_quadratic_funding(session_id, Address::ZERO, recipients[..2].to_vec()),
+        Err(QuadraticVotingError::InvalidProposalCount(_))
+    ));
+    contract
+        .enable_quadratic_funding(session_id, Address::ZERO, recipients.to_vec())
+        .unwrap();
+
+    // Anyone can fund the pool, with msg.value matching the amount
+    let sponsor = Address::from([9u8; 20]);
+    vm.set_sender(sponsor);
+    vm.set_value(U256::from(699));
+    assert!(matches!(
+        contract.fund_matching_pool(session_id, U256::from(700)),
+        Err(QuadraticVotingError::InvalidAmount(_))
+    ));
+    vm.set_value(U256::from(700));
+    contract
+        .fund_matching_pool(session_id, U256::from(700))
+        .unwrap();
+
+    // Contributions only open with the session window
+    let contribute = |contract: &mut QuadraticVoting, from: Address, proposal: u8, amount: u64| {
+        vm.set_sender(from);
+        vm.set_value(U256::from(amount));
+        contract.contribute(session_id, U16::from(proposal), U256::from(amount))
+    };
+    assert!(matches!(
+        contribute(&mut contract, alice, 1, 4),
+        Err(QuadraticVotingError::InvalidSession(_))
+    ));
+    vm.set_block_timestamp(now + 10);
+
+    // Project 1: 4 + 4 (Alice splits hers) -> (2 + 2)² − 8 = 8
+    contribute(&mut contract, alice, 1, 1).unwrap();
+    contribute(&mut contract, alice, 1, 3).unwrap();
+    contribute(&mut contract, bob, 1, 4).unwrap();
+    // Project 2: a single contributor -> 16 − 16 = 0
+    contribute(&mut contract, carol, 2, 16).unwrap();
+    // Project 3: 1 + 1 + 1 -> 3² − 3 = 6
+    for voter in [alice, bob, carol] {
+        contribute(&mut contract, voter, 3, 1).unwrap();
+    }
+    assert!(matches!(
+        contribute(&mut contract, sponsor, 1, 1),
+        Err(QuadraticVotingError::VoterNotRegistered(_))
+    ));
+    assert!(matches!(
+        contribute(&mut contract, alice, 4, 1),
+        Err(QuadraticVotingError::InvalidProposal(_))
+    ));
+
+    vm.set_value(U256::ZERO);
+    assert!(matches!(
+        contract.finalize_matching(session_id),
+        Err(QuadraticVotingError::InvalidSession(_))
+    ));
+
+    // The pool of 700 is split 8:0:6
+    vm.set_block_timestamp(now + 10 + 3600);
+    assert_eq!(contract.finalize_matching(session_id).unwrap(), U256::from(700));
+    let logs = vm.get_emitted_logs();
+    let (topics, data) = logs.last().unwrap();
+    assert_eq!(topics[0], MatchingFinalized::SIGNATURE_HASH);
+    let event = MatchingFinalized::decode_raw_log(topics.clone(), data, true).unwrap();
+    assert_eq!(event.total_matched, U256::from(700));
+    assert!(matches!(
+        contract.finalize_matching(session_id),
+        Err(QuadraticVotingError::InvalidSession(_))
+    ));
+    assert_eq!(
+        contract.get_matching_pool(session_id).unwrap(),
+        (true, Address::ZERO, U256::from(700), true, U256::from(700))
+    );
+    assert_eq!(
+        contract.get_proposal_funding(session_id, U16::from(1)).unwrap(),
+        (recipients[0], U256::from(8), U256::from(400), false)
+    );
+
+    // Finalized rounds can no longer be cancelled
+    vm.set_sender(admin);
+    assert!(matches!(
+        contract.cancel_session(session_id),
+        Err(QuadraticVotingError::InvalidSession(_))
+    ));
+
+    // Recipients claim their contributions plus their match
+    vm.set_balance(vm.contract_address(), U256::from(727));
+    vm.set_sender(alice);
+    assert!(matches!(
+        contract.claim(session_id, U16::from(1)),
+        Err(QuadraticVotingError::Unauthorized(_))
+    ));
+    for (index, expected) in [408u64, 16, 303].into_iter().enumerate() {
+        let proposal_id = U16::from(index + 1);
+        vm.set_sender(recipients[index]);
+        assert_eq!(
+            contract.claim(session_id, proposal_id).unwrap(),
+            U256::from(expected)
+        );
+        assert_eq!(vm.balance(recipients[index]), U256::from(expected));
+
+        let logs = vm.get_emitted_logs();
+        let (topics, data) = logs.last().unwrap();
+        assert_eq!(topics[0], FundsClaimed::SIGNATURE_HASH);
+        let event = FundsClaimed::decode_raw_log(topics.clone(), data, true).unwrap();
+        assert_eq!(event.recipient, recipients[index]);
+        assert_eq!(event.amount, U256::from(expected));
+    }
+    vm.set_sender(recipients[0]);
+    assert!(matches!(
+        contract.claim(session_id, U16::from(1)),
+        Err(QuadraticVotingError::InvalidAmount(_))
+    ));
+    // Nothing to refund in a round that paid out
+    vm.set_sender(sponsor);
+    assert!(matches!(
+        contract.refund(session_id),
+        Err(QuadraticVotingError::InvalidSession(_))
+    ));
+}
+
+#[test]
+fn test_quadratic_funding_refunds() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let voter = Address::from([2u8; 20]);
+    vm.set_sender(voter);
+    contract
+        .register_voter(identity_of(voter), Vec::new().into())
+        .unwrap();
+
+    let now = vm.block_timestamp();
+    let token = Address::from([0xAAu8; 20]);
+    let recipient = Address::from([0x11u8; 20]);
+    let sponsor = Address::from([9u8; 20]);
+    let mut sessions = Vec::new();
+    vm.set_sender(admin);
+    for _ in 0..2 {
+        let session_id = contract
+            .create_session(
+                "Grants".to_string(),
+                "Desc".to_string(),
+                U256::from(100),
+                U64::from(now + 10),
+                U64::from(3600),
+                vec![("Project".to_string(), "Desc".to_string())],
+                B256::ZERO,
+                U16::from(1),
+            )
+            .unwrap();
+        contract
+            .enable_quadratic_funding(session_id, token, vec![recipient])
+            .unwrap();
+        sessions.push(session_id);
+    }
+
+    // Mock ERC-20 accepting every transfer used below
+    let contract_address = vm.contract_address();
+    for (from, amount) in [(sponsor, 500u64), (voter, 25)] {
+        let amount = U256::from(amount);
+        vm.mock_call(
+            token,
+            IERC20::transferFromCall { from, to: contract_address, amount }.abi_encode(),
+            Ok(true.abi_encode()),
+        );
+        vm.mock_call(
+            token,
+            IERC20::transferCall { to: from, amount }.abi_encode(),
+            Ok(true.abi_encode()),
+        );
+    }
+    vm.mock_call(
+        token,
+        IERC20::transferCall { to: recipient, amount: U256::from(25) }.abi_encode(),
+        Ok(true.abi_encode()),
+    );
+
+    vm.set_sender(sponsor);
+    // Token rounds take no ETH
+    vm.set_value(U256::from(500));
+    assert!(matches!(
+        contract.fund_matching_pool(sessions[0], U256::from(500)),
+        Err(QuadraticVotingError::InvalidAmount(_))
+    ));
+    vm.set_value(U256::ZERO);
+    vm.set_block_timestamp(now + 10);
+    for &session_id in &sessions {
+        vm.set_sender(sponsor);
+        contract
+            .fund_matching_pool(session_id, U256::from(500))
+            .unwrap();
+        vm.set_sender(voter);
+        contract
+            .contribute(session_id, U16::from(1), U256::from(25))
+            .unwrap();
+    }
+
+    // A cancelled round refunds contributors and the pool
+    vm.set_sender(admin);
+    contract.cancel_session(sessions[0]).unwrap();
+    vm.set_sender(voter);
+    assert_eq!(contract.refund(sessions[0]).unwrap(), U256::from(25));
+    vm.set_sender(sponsor);
+    assert_eq!(contract.refund(sessions[0]).unwrap(), U256::from(500));
+    assert!(matches!(
+        contract.refund(sessions[0]),
+        Err(QuadraticVotingError::InvalidAmount(_))
+    ));
+
+    // A lone contributor earns no match, so the pool goes back to its funder
+    vm.set_block_timestamp(now + 10 + 3600);
+    assert_eq!(contract.finalize_matching(sessions[1]).unwrap(), U256::ZERO);
+    vm.set_sender(voter);
+    assert!(matches!(
+        contract.refund(sessions[1]),
+        Err(QuadraticVotingError::InvalidAmount(_))
+    ));
+    vm.set_sender(sponsor);
+    assert_eq!(contract.refund(sessions[1]).unwrap(), U256::from(500));
+    vm.set_sender(recipient);
+    assert_eq!(
+        contract.claim(sessions[1], U16::from(1)).unwrap(),
+        U256::from(25)
+    );
+}
+
+#[test]
+fn test_sweep_matching_dust() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let voters: Vec<Address> = (2u8..5).map(|b| Address::from([b; 20])).collect();
+    for &voter in &voters {
+        vm.set_sender(voter);
+        contract
+            .register_voter(identity_of(voter), Vec::new().into())
+            .unwrap();
+    }
+
+    let now = vm.block_timestamp();
+    vm.set_sender(admin);
+    let session_id = contract
+        .create_session(
+            "Grants".to_string(),
+            "Desc".to_string(),
+            U256::from(100),
+            U64::from(now + 10),
+            U64::from(3600),
+            vec![
+                ("Project 1".to_string(), "Desc".to_string()),
+                ("Project 2".to_string(), "Desc".to_string()),
+            ],
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+    let recipients = vec![Address::from([0x11u8; 20]), Address::from([0x12u8; 20])];
+    contract
+        .enable_quadratic_funding(session_id, Address::ZERO, recipients)
+        .unwrap();
+    vm.set_value(U256::from(10));
+    contract.fund_matching_pool(session_id, U256::from(10)).unwrap();
+
+    // Ideal matches 2 and 6 split a pool of 10 into 2.5 and 7.5, rounded down to 2 and 7
+    vm.set_block_timestamp(now + 10);
+    vm.set_value(U256::from(1));
+    for (index, &voter) in voters.iter().enumerate() {
+        vm.set_sender(voter);
+        if index < 2 {
+            contract.contribute(session_id, U16::from(1), U256::from(1)).unwrap();
+        }
+        contract.contribute(session_id, U16::from(2), U256::from(1)).unwrap();
+    }
+    vm.set_value(U256::ZERO);
+
+    vm.set_sender(admin);
+    assert!(matches!(
+        contract.sweep_matching_dust(session_id),
+        Err(QuadraticVotingError::InvalidSession(_))
+    ));
+    vm.set_block_timestamp(now + 10 + 3600);
+    assert_eq!(contract.finalize_matching(session_id).unwrap(), U256::from(9));
+
+    vm.set_sender(voters[0]);
+    assert!(matches!(
+        contract.sweep_matching_dust(session_id),
+        Err(QuadraticVotingError::Unauthorized(_))
+    ));
+    vm.set_balance(vm.contract_address(), U256::from(15));
+    vm.set_sender(admin);
+    assert_eq!(contract.sweep_matching_dust(session_id).unwrap(), U256::from(1));
+    assert_eq!(vm.balance(admin), U256::from(1));
+    let logs = vm.get_emitted_logs();
+    let (topics, data) = logs.last().unwrap();
+    assert_eq!(topics[0], MatchingDustSwept::SIGNATURE_HASH);
+    let event = MatchingDustSwept::decode_raw_log(topics.clone(), data, true).unwrap();
+    assert_eq!(event.to, admin);
+    assert_eq!(event.amount, U256::from(1));
+    assert!(matches!(
+        contract.sweep_matching_dust(session_id),
+        Err(QuadraticVotingError::InvalidAmount(_))
+    ));
+}
+
+/// Proposal submissions
+#[test]
+fn test_voters_submit_proposals_before_start() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let voter = Address::from([2u8; 20]);
+    vm.set_sender(voter);
+    contract
+        .register_voter(identity_of(voter), Vec::new().into())
+        .unwrap();
+
+    let now = vm.block_timestamp();
+    vm.set_sender(admin);
+    let session_id = contract
+        .create_session(
+            "Test".to_string(),
+            "Desc".to_string(),
+            U256::from(100),
+            U64::from(now + 100),
+            U64::from(3600),
+            vec![("Seed".to_string(), "From creator".to_string())],
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+
+    // Submissions are closed until the creator opens them
+    vm.set_sender(voter);
+    assert!(matches!(
+        contract.submit_proposal(session_id, "Idea".to_string(), "Desc".to_string()),
+        Err(QuadraticVotingError::InvalidSession(_))
+    ));
+    assert!(matches!(
+        contract.enable_proposal_submissions(session_id, U16::from(2), U256::ZERO),
+        Err(QuadraticVotingError::Unauthorized(_))
+    ));
+    vm.set_sender(admin);
+    contract
+        .enable_proposal_submissions(session_id, U16::from(2), U256::ZERO)
+        .unwrap();
+
+    vm.set_sender(voter);
+    let first = contract
+        .submit_proposal(session_id, "Idea".to_string(), "Desc".to_string())
+        .unwrap();
+    assert_eq!(first, U16::from(2));
+    let logs = vm.get_emitted_logs();
+    let (topics, data) = logs.last().unwrap();
+    assert_eq!(topics[0], ProposalSubmitted::SIGNATURE_HASH);
+    let event = ProposalSubmitted::decode_raw_log(topics.clone(), data, true).unwrap();
+    assert_eq!(event.proposal_id, 2);
+    assert_eq!(event.proposer, voter);
+
+    let spam = contract
+        .submit_proposal(session_id, "Buy my token".to_string(), "Spam".to_string())
+        .unwrap();
+    // The per-session cap counts every submission
+    assert!(matches!(
+        contract.submit_proposal(session_id, "Third".to_string(), "Desc".to_string()),
+        Err(QuadraticVotingError::InvalidProposalCount(_))
+    ));
+
+    // Without a bond, only registered voters may submit
+    vm.set_sender(admin);
+    contract
+        .enable_proposal_submissions(session_id, U16::from(5), U256::ZERO)
+        .unwrap();
+    vm.set_sender(Address::from([3u8; 20]));
+    assert!(matches!(
+        contract.submit_proposal(session_id, "Outsider".to_string(), "Desc".to_string()),
+        Err(QuadraticVotingError::VoterNotRegistered(_))
+    ));
+
+    // Moderation: only the creator side, and never for the creator's own proposals
+    vm.set_sender(voter);
+    assert!(matches!(
+        contract.reject_proposal(session_id, spam),
+        Err(QuadraticVotingError::Unauthorized(_))
+    ));
+    vm.set_sender(admin);
+    assert!(matches!(
+        contract.reject_proposal(session_id, U16::from(1)),
+        Err(QuadraticVotingError::InvalidProposal(_))
+    ));
+    contract.reject_proposal(session_id, spam).unwrap();
+    let logs = vm.get_emitted_logs();
+    let (topics, data) = logs.last().unwrap();
+    assert_eq!(topics[0], ProposalRejected::SIGNATURE_HASH);
+    let event = ProposalRejected::decode_raw_log(topics.clone(), data, true).unwrap();
+    assert_eq!(event.proposal_id, spam.to::<u16>());
+    assert_eq!(
+        contract.get_proposal_submission(session_id, spam).unwrap(),
+        (voter, U256::ZERO, true)
+    );
+
+    let proposals = contract.get_session_proposals(session_id, U16::ZERO, U16::MAX).unwrap();
+    let ids: Vec<U16> = proposals.iter().map(|p| p.0).collect();
+    assert_eq!(ids, vec![U16::from(1), first]);
+    assert_eq!(proposals[1].1, "Idea");
+
+    // The phase ends when voting starts; rejected proposals cannot receive votes
+    vm.set_block_timestamp(now + 100);
+    vm.set_sender(voter);
+    assert!(matches!(
+        contract.submit_proposal(session_id, "Late".to_string(), "Desc".to_string()),
+        Err(QuadraticVotingError::InvalidSession(_))
+    ));
+    assert!(matches!(
+        contract.vote(session_id, vec![spam], vec![U64::from(1)]),
+        Err(QuadraticVotingError::InvalidProposal(_))
+    ));
+    contract
+        .vote(session_id, vec![first], vec![U64::from(2)])
+        .unwrap();
+    vm.set_sender(admin);
+    assert!(matches!(
+        contract.reject_proposal(session_id, first),
+        Err(QuadraticVotingError::InvalidSession(_))
+    ));
+}
+
+#[test]
+fn test_bonded_proposal_submissions() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let now = vm.block_timestamp();
+    let creator = Address::from([5u8; 20]);
+    contract.grant_role(SESSION_CREATOR_ROLE, creator).unwrap();
+    vm.set_sender(creator);
+    let session_id = contract
+        .create_session(
+            "Test".to_string(),
+            "Desc".to_string(),
+            U256::from(100),
+            U64::from(now + 100),
+            U64::from(3600),
+            vec![("Seed".to_string(), "Desc".to_string())],
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+    let bond = U256::from(1000);
+    contract
+        .enable_proposal_submissions(session_id, U16::from(5), bond)
+        .unwrap();
+
+    // Unregistered submitters must post exactly the bond
+    let honest = Address::from([6u8; 20]);
+    let spammer = Address::from([7u8; 20]);
+    vm.set_sender(honest);
+    vm.set_value(U256::from(999));
+    assert!(matches!(
+        contract.submit_proposal(session_id, "Idea".to_string(), "Desc".to_string()),
+        Err(QuadraticVotingError::InvalidAmount(_))
+    ));
+    vm.set_value(bond);
+    let idea = contract
+        .submit_proposal(session_id, "Idea".to_string(), "Desc".to_string())
+        .unwrap();
+    vm.set_sender(spammer);
+    let spam = contract
+        .submit_proposal(session_id, "Spam".to_string(), "Spam".to_string())
+        .unwrap();
+    vm.set_value(U256::ZERO);
+    vm.set_balance(vm.contract_address(), bond * U256::from(2));
+
+    // A rejected proposal's bond is credited to the session creator, who withdraws it
+    vm.set_sender(creator);
+    contract.reject_proposal(session_id, spam).unwrap();
+    assert_eq!(vm.balance(creator), U256::ZERO);
+    assert_eq!(contract.get_forfeited_bonds(creator), bond);
+    vm.set_sender(spammer);
+    assert!(matches!(
+        contract.withdraw_forfeited_bonds(),
+        Err(QuadraticVotingError::InvalidAmount(_))
+    ));
+    vm.set_sender(creator);
+    assert_eq!(contract.withdraw_forfeited_bonds().unwrap(), bond);
+    assert_eq!(vm.balance(creator), bond);
+    assert_eq!(contract.get_forfeited_bonds(creator), U256::ZERO);
+    let logs = vm.get_emitted_logs();
+    let (topics, data) = logs.last().unwrap();
+    let event = ForfeitedBondsWithdrawn::decode_raw_log(topics.clone(), data, true).unwrap();
+    assert_eq!((event.recipient, event.amount), (creator, bond));
+
+    // Accepted bonds come back once voting starts
+    vm.set_sender(honest);
+    assert!(matches!(
+        contract.reclaim_bond(session_id, idea),
+        Err(QuadraticVotingError::InvalidSession(_))
+    ));
+    vm.set_block_timestamp(now + 100);
+    vm.set_sender(spammer);
+    assert!(matches!(
+        contract.reclaim_bond(session_id, spam),
+        Err(QuadraticVotingError::InvalidAmount(_))
+    ));
+    assert!(matches!(
+        contract.reclaim_bond(session_id, idea),
+        Err(QuadraticVotingError::Unauthorized(_))
+    ));
+    vm.set_sender(honest);
+    assert_eq!(contract.reclaim_bond(session_id, idea).unwrap(), bond);
+    assert_eq!(vm.balance(honest), bond);
+    assert!(matches!(
+        contract.reclaim_bond(session_id, idea),
+        Err(QuadraticVotingError::InvalidAmount(_))
+    ));
+}
+
+/// Large sessions
+#[test]
+fn test_hundreds_of_proposals_with_pagination() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let voter = Address::from([2u8; 20]);
+    vm.set_sender(voter);
+    contract
+        .register_voter(identity_of(voter), Vec::new().into())
+        .unwrap();
+
+    vm.set_sender(admin);
+    let session_id = contract
+        .create_session(
+            "Big".to_string(),
+            "Desc".to_string(),
+            U256::from(100),
+            U64::ZERO,
+            U64::from(3600),
+            (1..=300)
+                .map(|i| (format!("Proposal {i}"), "Desc".to_string()))
+                .collect(),
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+    assert_eq!(contract.get_session(session_id).unwrap().7, U16::from(300));
+
+    // Pages cover consecutive ids and stop at the last proposal
+    let page = contract
+        .get_session_proposals(session_id, U16::from(100), U16::from(50))
+        .unwrap();
+    assert_eq!(page.len(), 50);
+    assert_eq!(page[0].0, U16::from(101));
+    assert_eq!(page[0].1, "Proposal 101");
+    assert_eq!(page[49].0, U16::from(150));
+    let tail = contract
+        .get_session_proposals(session_id, U16::from(280), U16::from(50))
+        .unwrap();
+    assert_eq!(tail.len(), 20);
+    assert_eq!(tail[19].0, U16::from(300));
+    assert!(contract
+        .get_session_proposals(session_id, U16::from(300), U16::from(50))
+        .unwrap()
+        .is_empty());
+
+    // Proposals beyond the old 255 limit can be voted on and win
+    vm.set_sender(voter);
+    contract
+        .vote(session_id, vec![U16::from(299), U16::from(3)], vec![U64::from(5), U64::from(4)])
+        .unwrap();
+    let (winner, count, max_votes, total, _) = contract.get_session_results(session_id).unwrap();
+    assert_eq!(winner, U16::from(299));
+    assert_eq!(count, U16::from(300));
+    assert_eq!(max_votes, U64::from(5));
+    assert_eq!(total, U64::from(9));
+
+    // Lowering the leader's votes hands the lead to the next best proposal
+    contract
+        .vote(session_id, vec![U16::from(299)], vec![U64::from(1)])
+        .unwrap();
+    let (winner, _, max_votes, total, _) = contract.get_session_results(session_id).unwrap();
+    assert_eq!(winner, U16::from(3));
+    assert_eq!(max_votes, U64::from(4));
+    assert_eq!(total, U64::from(5));
+}
+
+#[test]
+fn test_leader_settles_after_losing_votes() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let session_id = contract
+        .create_session(
+            "Test".to_string(),
+            "Desc".to_string(),
+            U256::from(100),
+            U64::ZERO,
+            U64::from(3600),
+            (1..=4)
+                .map(|i| (format!("Proposal {i}"), "Desc".to_string()))
+                .collect(),
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+    let voter = Address::from([2u8; 20]);
+    vm.set_sender(voter);
+    contract
+        .register_voter(identity_of(voter), Vec::new().into())
+        .unwrap();
+    let leader = |contract: &QuadraticVoting| {
+        let (winner, _, max_votes, _, _) = contract.get_session_results(session_id).unwrap();
+        (winner, max_votes)
+    };
+
+    contract
+        .vote(
+            session_id,
+            vec![U16::from(1), U16::from(2), U16::from(3)],
+            vec![U64::from(5), U64::from(5), U64::from(2)],
+        )
+        .unwrap();
+    assert_eq!(leader(&contract), (U16::from(1), U64::from(5)));
+
+    // The leader dropping leaves proposal 2 ahead, found by rescanning
+    contract
+        .vote(session_id, vec![U16::from(1)], vec![U64::from(1)])
+        .unwrap();
+    assert_eq!(leader(&contract), (U16::from(2), U64::from(5)));
+
+    // Back at the old top score, the lowest tied ID leads again
+    contract
+        .vote(session_id, vec![U16::from(1)], vec![U64::from(5)])
+        .unwrap();
+    assert_eq!(leader(&contract), (U16::from(1), U64::from(5)));
+
+    // Passing the old top score settles the lead on the new proposal
+    contract
+        .vote(session_id, vec![U16::from(1)], vec![U64::from(2)])
+        .unwrap();
+    contract
+        .vote(session_id, vec![U16::from(4)], vec![U64::from(6)])
+        .unwrap();
+    assert_eq!(leader(&contract), (U16::from(4), U64::from(6)));
+
+    // The rescanned leader is stored when the session is finalized
+    contract
+        .vote(session_id, vec![U16::from(4)], vec![U64::from(3)])
+        .unwrap();
+    vm.set_block_timestamp(vm.block_timestamp() + 3600);
+    contract.finalize_session(session_id).unwrap();
+    assert_eq!(leader(&contract), (U16::from(2), U64::from(5)));
+}
+
+/// Proposal text
+#[test]
+fn test_long_and_non_ascii_text_round_trips() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    // Well past the old 32-byte limit, with multibyte characters straddling byte 32
+    let name = "Municipal budget 2025 — participatory round for every district".to_string();
+    let description = "ipfs://bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi".repeat(10);
+    let titles = [
+        "Ремонт школьного спортзала и новая площадка".to_string(),
+        "公園の整備と図書館の開館時間の延長について".to_string(),
+        "🌳🌳🌳🌳🌳🌳🌳🌳 Plant trees along the river 🌳".to_string(),
+        "x".repeat(256),
+    ];
+    let session_id = contract
+        .create_session(
+            name.clone(),
+            description.clone(),
+            U256::from(100),
+            U64::ZERO,
+            U64::from(3600),
+            titles
+                .iter()
+                .map(|title| (title.clone(), "Описание проекта".to_string()))
+                .collect(),
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+
+    let session = contract.get_session(session_id).unwrap();
+    assert_eq!(session.0, name);
+    assert_eq!(session.1, description);
+
+    let logs = vm.get_emitted_logs();
+    let (topics, data) = logs
+        .iter()
+        .find(|(topics, _)| topics[0] == SessionCreated::SIGNATURE_HASH)
+        .unwrap();
+    let event = SessionCreated::decode_raw_log(topics.clone(), data, true).unwrap();
+    assert_eq!(event.name, name);
+
+    let proposals = contract
+        .get_session_proposals(session_id, U16::ZERO, U16::MAX)
+        .unwrap();
+    for (proposal, title) in proposals.iter().zip(titles.iter()) {
+        assert_eq!(&proposal.1, title);
+        assert_eq!(proposal.2, "Описание проекта");
+    }
+}
+
+#[test]
+fn test_content_too_long_is_rejected() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let create = |contract: &mut QuadraticVoting, name: String, description: String, title: String| {
+        contract.create_session(
+            name,
+            description,
+            U256::from(100),
+            U64::ZERO,
+            U64::from(3600),
+            vec![(title, "Desc".to_string())],
+            B256::ZERO,
+            U16::from(1),
+        )
+    };
+
+    // Limits are in bytes: 86 three-byte characters exceed 256 bytes
+    for (name, description, title) in [
+        ("x".repeat(257), "Desc".to_string(), "Title".to_string()),
+        ("Name".to_string(), "x".repeat(1025), "Title".to_string()),
+        ("Name".to_string(), "Desc".to_string(), "漢".repeat(86)),
+    ] {
+        assert!(matches!(
+            create(&mut contract, name, description, title),
+            Err(QuadraticVotingError::ContentTooLong(_))
+        ));
+    }
+
+    // Nothing was stored by the rejected calls
+    let session_id = create(
+        &mut contract,
+        "Name".to_string(),
+        "Desc".to_string(),
+        "漢".repeat(85),
+    )
+    .unwrap();
+    assert_eq!(session_id, U64::from(1));
+
+    // Submitted proposals are held to the same limits
+    let voter = Address::from([2u8; 20]);
+    vm.set_sender(voter);
+    contract
+        .register_voter(identity_of(voter), Vec::new().into())
+        .unwrap();
+    vm.set_sender(admin);
+    let scheduled = contract
+        .create_session(
+            "Scheduled".to_string(),
+            "Desc".to_string(),
+            U256::from(100),
+            U64::from(vm.block_timestamp() + 100),
+            U64::from(3600),
+            vec![("Seed".to_string(), "Desc".to_string())],
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+    contract
+        .enable_proposal_submissions(scheduled, U16::from(5), U256::ZERO)
+        .unwrap();
+    vm.set_sender(voter);
+    assert!(matches!(
+        contract.submit_proposal(scheduled, "Idea".to_string(), "é".repeat(513)),
+        Err(QuadraticVotingError::ContentTooLong(_))
+    ));
+    contract
+        .submit_proposal(scheduled, "Idea".to_string(), "é".repeat(512))
+        .unwrap();
+}
+
+/// Tie-breaking
+#[test]
+fn test_leaderboard_groups_ties() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let session_id = contract
+        .create_session(
+            "Test".to_string(),
+            "Desc".to_string(),
+            U256::from(100),
+            U64::ZERO,
+            U64::from(3600),
+            vec![
+                ("Proposal 1".to_string(), "Desc".to_string()),
+                ("Proposal 2".to_string(), "Desc".to_string()),
+                ("Proposal 3".to_string(), "Desc".to_string()),
+            ],
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+
+    let voters: Vec<Address> = (10u8..13).map(|b| Address::from([b; 20])).collect();
+    for &voter in &voters {
+        vm.set_sender(voter);
+        contract
+            .register_voter(identity_of(voter), Vec::new().into())
+            .unwrap();
+    }
+
+    // Before any votes every proposal shares first place and there is no winner
+    let leaderboard = contract.get_leaderboard(session_id).unwrap();
+    assert!(leaderboard.iter().all(|&(_, votes, rank)| votes == I128::ZERO && rank == U16::from(1)));
+    assert_eq!(contract.get_session_results(session_id).unwrap().0, U16::ZERO);
+
+    vm.set_sender(voters[0]);
+    contract
+        .vote(session_id, vec![U16::from(3)], vec![U64::from(4)])
+        .unwrap();
+    vm.set_sender(voters[1]);
+    contract
+        .vote(session_id, vec![U16::from(2)], vec![U64::from(4)])
+        .unwrap();
+    vm.set_sender(voters[2]);
+    contract
+        .vote(session_id, vec![U16::from(1)], vec![U64::from(1)])
+        .unwrap();
+
+    // Tied proposals share a rank, lowest ID first by default, and the next rank skips
+    assert_eq!(
+        contract.get_leaderboard(session_id).unwrap(),
+        vec![
+            (U16::from(2), I128::try_from(4).unwrap(), U16::from(1)),
+            (U16::from(3), I128::try_from(4).unwrap(), U16::from(1)),
+            (U16::from(1), I128::try_from(1).unwrap(), U16::from(3)),
+        ]
+    );
+    assert_eq!(contract.get_session_results(session_id).unwrap().0, U16::from(2));
+
+    vm.set_sender(admin);
+    contract.cancel_session(session_id).unwrap();
+    assert!(matches!(
+        contract.get_leaderboard(session_id),
+        Err(QuadraticVotingError::InvalidSession(_))
+    ));
+}
+
+#[test]
+fn test_tie_break_earliest_and_none() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let session_id = contract
+        .create_session(
+            "Test".to_string(),
+            "Desc".to_string(),
+            U256::from(100),
+            U64::ZERO,
+            U64::from(3600),
+            vec![
+                ("Proposal 1".to_string(), "Desc".to_string()),
+                ("Proposal 2".to_string(), "Desc".to_string()),
+                ("Proposal 3".to_string(), "Desc".to_string()),
+            ],
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+
+    let voters: Vec<Address> = (10u8..13).map(|b| Address::from([b; 20])).collect();
+    for &voter in &voters {
+        vm.set_sender(voter);
+        contract
+            .register_voter(identity_of(voter), Vec::new().into())
+            .unwrap();
+    }
+
+    vm.set_sender(admin);
+    assert!(matches!(
+        contract.set_tie_break(session_id, TIE_BREAK_NONE + 1),
+        Err(QuadraticVotingError::InvalidMode(_))
+    ));
+    contract.set_tie_break(session_id, TIE_BREAK_EARLIEST).unwrap();
+
+    // Proposal 3 reaches 4 votes before proposal 2 does
+    vm.set_sender(voters[0]);
+    contract
+        .vote(session_id, vec![U16::from(3)], vec![U64::from(4)])
+        .unwrap();
+    vm.set_sender(voters[1]);
+    contract
+        .vote(session_id, vec![U16::from(2)], vec![U64::from(4)])
+        .unwrap();
+    assert_eq!(contract.get_session_results(session_id).unwrap().0, U16::from(3));
+    let leaderboard = contract.get_leaderboard(session_id).unwrap();
+    assert_eq!(leaderboard[0].0, U16::from(3));
+    assert_eq!(leaderboard[1].0, U16::from(2));
+
+    // Dropping to 3 and climbing back to 4 moves proposal 3 behind proposal 2
+    vm.set_sender(voters[0]);
+    contract
+        .vote(session_id, vec![U16::from(3)], vec![U64::from(3)])
+        .unwrap();
+    contract
+        .vote(session_id, vec![U16::from(3)], vec![U64::from(4)])
+        .unwrap();
+    assert_eq!(contract.get_session_results(session_id).unwrap().0, U16::from(2));
+
+    // The policy is fixed once votes are counted
+    vm.set_sender(admin);
+    assert!(matches!(
+        contract.set_tie_break(session_id, TIE_BREAK_NONE),
+        Err(QuadraticVotingError::InvalidSession(_))
+    ));
+
+    let session_id = contract
+        .create_session(
+            "No ties".to_string(),
+            "Desc".to_string(),
+            U256::from(100),
+            U64::ZERO,
+            U64::from(3600),
+            vec![
+                ("Proposal 1".to_string(), "Desc".to_string()),
+                ("Proposal 2".to_string(), "Desc".to_string()),
+            ],
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+    contract.set_tie_break(session_id, TIE_BREAK_NONE).unwrap();
+    vm.set_sender(voters[0]);
+    contract
+        .vote(session_id, vec![U16::from(1), U16::from(2)], vec![U64::from(2), U64::from(2)])
+        .unwrap();
+    assert_eq!(contract.get_session_results(session_id).unwrap().0, U16::ZERO);
+    vm.set_sender(voters[1]);
+    contract
+        .vote(session_id, vec![U16::from(2)], vec![U64::from(1)])
+        .unwrap();
+    assert_eq!(contract.get_session_results(session_id).unwrap().0, U16::from(2));
+}
+
+#[test]
+fn test_creator_resolves_tie() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let session_id = contract
+        .create_session(
+            "Test".to_string(),
+            "Desc".to_string(),
+            U256::from(100),
+            U64::ZERO,
+            U64::from(3600),
+            vec![
+                ("Proposal 1".to_string(), "Desc".to_string()),
+                ("Proposal 2".to_string(), "Desc".to_string()),
+                ("Proposal 3".to_string(), "Desc".to_string()),
+            ],
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+
+    let voters: Vec<Address> = (10u8..13).map(|b| Address::from([b; 20])).collect();
+    for &voter in &voters {
+        vm.set_sender(voter);
+        contract
+            .register_voter(identity_of(voter), Vec::new().into())
+            .unwrap();
+    }
+
+    vm.set_sender(admin);
+    contract.set_tie_break(session_id, TIE_BREAK_CREATOR).unwrap();
+
+    vm.set_sender(voters[0]);
+    contract
+        .vote(session_id, vec![U16::from(1), U16::from(3)], vec![U64::from(5), U64::from(2)])
+        .unwrap();
+    vm.set_sender(voters[1]);
+    contract
+        .vote(session_id, vec![U16::from(2)], vec![U64::from(5)])
+        .unwrap();
+    assert_eq!(contract.get_session_results(session_id).unwrap().0, U16::ZERO);
+
+    // Only the creator, and only once voting is over
+    vm.set_sender(admin);
+    assert!(matches!(
+        contract.resolve_tie(session_id, U16::from(2)),
+        Err(QuadraticVotingError::InvalidSession(_))
+    ));
+    vm.set_block_timestamp(vm.block_timestamp() + 3600);
+    vm.set_sender(voters[0]);
+    assert!(matches!(
+        contract.resolve_tie(session_id, U16::from(2)),
+        Err(QuadraticVotingError::Unauthorized(_))
+    ));
+
+    // Proposal 3 is not part of the tie
+    vm.set_sender(admin);
+    assert!(matches!(
+        contract.resolve_tie(session_id, U16::from(3)),
+        Err(QuadraticVotingError::InvalidProposal(_))
+    ));
+    contract.resolve_tie(session_id, U16::from(2)).unwrap();
+    let logs = vm.get_emitted_logs();
+    let (topics, data) = logs.last().unwrap();
+    let event = TieResolved::decode_raw_log(topics.clone(), data, true).unwrap();
+    assert_eq!(event.proposal_id, 2);
+
+    assert_eq!(contract.get_tie_break(session_id), (TIE_BREAK_CREATOR, U16::from(2)));
+    assert_eq!(contract.get_session_results(session_id).unwrap().0, U16::from(2));
+}
+
+/// Session outcomes
+#[test]
+fn test_quorum_and_threshold_outcomes() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let session_id = contract
+        .create_session(
+            "Test".to_string(),
+            "Desc".to_string(),
+            U256::from(100),
+            U64::ZERO,
+            U64::from(3600),
+            vec![
+                ("Proposal 1".to_string(), "Desc".to_string()),
+                ("Proposal 2".to_string(), "Desc".to_string()),
+                ("Proposal 3".to_string(), "Desc".to_string()),
+            ],
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+
+    let voters: Vec<Address> = (10u8..13).map(|b| Address::from([b; 20])).collect();
+    for &voter in &voters {
+        vm.set_sender(voter);
+        contract
+            .register_voter(identity_of(voter), Vec::new().into())
+            .unwrap();
+    }
+
+
+    vm.set_sender(voters[0]);
+    assert!(matches!(
+        contract.set_session_rules(session_id, U64::from(2), U256::from(10), U64::from(3)),
+        Err(QuadraticVotingError::Unauthorized(_))
+    ));
+    vm.set_sender(admin);
+    contract
+        .set_session_rules(session_id, U64::from(2), U256::from(10), U64::from(3))
+        .unwrap();
+
+    // One voter is short of the two-voter quorum
+    vm.set_sender(voters[0]);
+    contract
+        .vote(session_id, vec![U16::from(1)], vec![U64::from(2)])
+        .unwrap();
+    let results = contract.get_session_results(session_id).unwrap();
+    assert_eq!((results.0, results.4), (U16::ZERO, OUTCOME_FAILED_QUORUM));
+
+    // Two voters but only 5 of the 10 required credits
+    vm.set_sender(voters[1]);
+    contract
+        .vote(session_id, vec![U16::from(2)], vec![U64::from(1)])
+        .unwrap();
+    assert_eq!(
+        contract.get_session_rules(session_id),
+        (U64::from(2), U256::from(10), U64::from(3), U64::from(2), U256::from(5), false)
+    );
+    assert_eq!(contract.get_session_results(session_id).unwrap().4, OUTCOME_FAILED_QUORUM);
+
+    vm.set_sender(voters[0]);
+    contract
+        .vote(session_id, vec![U16::from(1)], vec![U64::from(4)])
+        .unwrap();
+    let results = contract.get_session_results(session_id).unwrap();
+    assert_eq!((results.0, results.4), (U16::from(1), OUTCOME_PASSED));
+
+    // Withdrawing every vote takes a voter out of the turnout
+    contract
+        .vote(session_id, vec![U16::from(1)], vec![U64::ZERO])
+        .unwrap();
+    assert_eq!(contract.get_session_rules(session_id).3, U64::from(1));
+    assert_eq!(contract.get_session_results(session_id).unwrap().4, OUTCOME_FAILED_QUORUM);
+
+    // Quorum is met again, but the top proposals have 2 of the 3 votes they need
+    vm.set_sender(voters[2]);
+    contract
+        .vote(
+            session_id,
+            vec![U16::from(1), U16::from(2), U16::from(3)],
+            vec![U64::from(2), U64::from(1), U64::from(2)],
+        )
+        .unwrap();
+    assert_eq!(contract.get_session_rules(session_id).4, U256::from(10));
+    let results = contract.get_session_results(session_id).unwrap();
+    assert_eq!((results.0, results.2, results.4), (U16::ZERO, U64::from(2), OUTCOME_NO_WINNER));
+
+    // Rules are fixed once votes are counted
+    vm.set_sender(admin);
+    assert!(matches!(
+        contract.set_session_rules(session_id, U64::ZERO, U256::ZERO, U64::ZERO),
+        Err(QuadraticVotingError::InvalidSession(_))
+    ));
+}
+
+#[test]
+fn test_finalize_session_freezes_outcome() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let session_id = contract
+        .create_session(
+            "Test".to_string(),
+            "Desc".to_string(),
+            U256::from(100),
+            U64::ZERO,
+            U64::from(3600),
+            vec![
+                ("Proposal 1".to_string(), "Desc".to_string()),
+                ("Proposal 2".to_string(), "Desc".to_string()),
+                ("Proposal 3".to_string(), "Desc".to_string()),
+            ],
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+
+    let voters: Vec<Address> = (10u8..13).map(|b| Address::from([b; 20])).collect();
+    for &voter in &voters {
+        vm.set_sender(voter);
+        contract
+            .register_voter(identity_of(voter), Vec::new().into())
+            .unwrap();
+    }
+
+
+    vm.set_sender(admin);
+    contract.set_tie_break(session_id, TIE_BREAK_CREATOR).unwrap();
+    vm.set_sender(voters[0]);
+    contract
+        .vote(session_id, vec![U16::from(1)], vec![U64::from(3)])
+        .unwrap();
+    vm.set_sender(voters[1]);
+    contract
+        .vote(session_id, vec![U16::from(2)], vec![U64::from(3)])
+        .unwrap();
+    assert_eq!(contract.get_session_results(session_id).unwrap().4, OUTCOME_TIED);
+
+    assert!(matches!(
+        contract.finalize_session(session_id),
+        Err(QuadraticVotingError::InvalidSession(_))
+    ));
+    vm.set_block_timestamp(vm.block_timestamp() + 3600);
+
+    // An unresolved creator tie can only be frozen by the creator
+    assert!(matches!(
+        contract.finalize_session(session_id),
+        Err(QuadraticVotingError::Unauthorized(_))
+    ));
+    vm.set_sender(admin);
+    contract.resolve_tie(session_id, U16::from(1)).unwrap();
+
+    // Anyone can finalize once the tie is settled
+    vm.set_sender(voters[2]);
+    assert_eq!(contract.finalize_session(session_id).unwrap(), OUTCOME_PASSED);
+    let logs = vm.get_emitted_logs();
+    let (topics, data) = &logs[logs.len() - 2];
+    assert_eq!(topics[0], SessionFinalized::SIGNATURE_HASH);
+    let event = SessionFinalized::decode_raw_log(topics.clone(), data, true).unwrap();
+    assert_eq!((event.outcome, event.winner_id), (OUTCOME_PASSED, 1));
+    let (topics, data) = logs.last().unwrap();
+    let event = WinnersSelected::decode_raw_log(topics.clone(), data, true).unwrap();
+    assert_eq!(event.proposal_ids, vec![1]);
+
+    assert!(contract.get_session_rules(session_id).5);
+    let results = contract.get_session_results(session_id).unwrap();
+    assert_eq!((results.0, results.4), (U16::from(1), OUTCOME_PASSED));
+
+    vm.set_sender(admin);
+    assert!(contract.finalize_session(session_id).is_err());
+    assert!(contract.resolve_tie(session_id, U16::from(2)).is_err());
+    assert!(contract.cancel_session(session_id).is_err());
+    assert!(contract
+        .extend_session(session_id, U64::from(vm.block_timestamp() + 3600))
+        .is_err());
+}
+
+/// Against votes
+#[test]
+fn test_against_votes_rank_by_net_score() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let session_id = contract
+        .create_session(
+            "Test".to_string(),
+            "Desc".to_string(),
+            U256::from(100),
+            U64::ZERO,
+            U64::from(3600),
+            vec![
+                ("Proposal 1".to_string(), "Desc".to_string()),
+                ("Proposal 2".to_string(), "Desc".to_string()),
+                ("Proposal 3".to_string(), "Desc".to_string()),
+            ],
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+
+    let voters: Vec<Address> = (10u8..13).map(|b| Address::from([b; 20])).collect();
+    for &voter in &voters {
+        vm.set_sender(voter);
+        contract
+            .register_voter(identity_of(voter), Vec::new().into())
+            .unwrap();
+    }
+
+
+    let intensities = |values: &[i64]| -> Vec<I64> {
+        values.iter().map(|&v| I64::try_from(v).unwrap()).collect()
+    };
+
+    // Opposition costs the same quadratic credits as support
+    vm.set_sender(voters[0]);
+    contract
+        .vote_with_intensities(session_id, vec![U16::from(1), U16::from(2)], intensities(&[5, -3]))
+        .unwrap();
+    let (_, spent, _) = contract
+        .get_voter_session_credits(session_id, voters[0])
+        .unwrap();
+    assert_eq!(spent, U256::from(34));
+
+    vm.set_sender(voters[1]);
+    contract
+        .vote_with_intensities(session_id, vec![U16::from(1), U16::from(2)], intensities(&[-4, 6]))
+        .unwrap();
+
+    // Proposal 1 has more support (5) but proposal 2 has the higher net score (3 vs 1)
+    let proposals = contract.get_session_proposals(session_id, U16::ZERO, U16::MAX).unwrap();
+    assert_eq!(
+        (proposals[0].3, proposals[0].4, proposals[0].5),
+        (U64::from(5), U64::from(4), I128::try_from(1).unwrap())
+    );
+    assert_eq!(
+        (proposals[1].3, proposals[1].4, proposals[1].5),
+        (U64::from(6), U64::from(3), I128::try_from(3).unwrap())
+    );
+    let (winner, _, max_net, total, _) = contract.get_session_results(session_id).unwrap();
+    assert_eq!((winner, max_net, total), (U16::from(2), U64::from(3), U64::from(18)));
+
+    // A plain vote replaces both the intensity and the direction
+    vm.set_sender(voters[0]);
+    contract
+        .vote(session_id, vec![U16::from(2)], vec![U64::from(1)])
+        .unwrap();
+    let proposals = contract.get_session_proposals(session_id, U16::ZERO, U16::MAX).unwrap();
+    assert_eq!((proposals[1].3, proposals[1].4), (U64::from(7), U64::ZERO));
+
+    // Opposed proposals rank below untouched ones, and no positive net score means no winner
+    vm.set_sender(voters[1]);
+    contract
+        .vote_with_intensities(session_id, vec![U16::from(1), U16::from(2)], intensities(&[-6, -7]))
+        .unwrap();
+    assert_eq!(
+        contract.get_leaderboard(session_id).unwrap(),
+        vec![
+            (U16::from(3), I128::ZERO, U16::from(1)),
+            (U16::from(1), I128::try_from(-1).unwrap(), U16::from(2)),
+            (U16::from(2), I128::try_from(-6).unwrap(), U16::from(3)),
+        ]
+    );
+    let results = contract.get_session_results(session_id).unwrap();
+    assert_eq!((results.0, results.2, results.4), (U16::ZERO, U64::ZERO, OUTCOME_NO_WINNER));
+}
+
+#[test]
+fn test_delegate_votes_against() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let delegator = Address::from([2u8; 20]);
+    let delegate = Address::from([3u8; 20]);
+    for voter in [delegator, delegate] {
+        vm.set_sender(voter);
+        contract
+            .register_voter(identity_of(voter), Vec::new().into())
+            .unwrap();
+    }
+
+    vm.set_sender(admin);
+    let session_id = contract
+        .create_session(
+            "Test".to_string(),
+            "Desc".to_string(),
+            U256::from(100),
+            U64::ZERO,
+            U64::from(3600),
+            vec![
+                ("Proposal 1".to_string(), "Desc".to_string()),
+                ("Proposal 2".to_string(), "Desc".to_string()),
+            ],
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+    vm.set_sender(delegator);
+    contract.delegate(session_id, delegate).unwrap();
+
+    // The against flags must match the votes one to one
+    vm.set_sender(delegate);
+    assert!(matches!(
+        contract.vote_as_delegate(session_id, delegator, vec![U16::from(1)], vec![U64::from(3)], vec![true, false]),
+        Err(QuadraticVotingError::InvalidVoteCount(_))
+    ));
+    contract
+        .vote_as_delegate(
+            session_id,
+            delegator,
+            vec![U16::from(1), U16::from(2)],
+            vec![U64::from(3), U64::from(2)],
+            vec![true, false],
+        )
+        .unwrap();
+
+    let proposals = contract.get_session_proposals(session_id, U16::ZERO, U16::MAX).unwrap();
+    assert_eq!((proposals[0].3, proposals[0].4), (U64::ZERO, U64::from(3)));
+    assert_eq!((proposals[1].3, proposals[1].4), (U64::from(2), U64::ZERO));
+    let (_, spent, _) = contract
+        .get_voter_session_credits(session_id, delegator)
+        .unwrap();
+    assert_eq!(spent, U256::from(13));
+}
+
+#[test]
+fn test_reveal_votes_against() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let voter = Address::from([2u8; 20]);
+    vm.set_sender(voter);
+    contract
+        .register_voter(identity_of(voter), Vec::new().into())
+        .unwrap();
+
+    vm.set_sender(admin);
+    let session_id = contract
+        .create_session(
+            "Private".to_string(),
+            "Desc".to_string(),
+            U256::from(100),
+            U64::from(100),
+            U64::from(100),
+            vec![("Proposal".to_string(), "Desc".to_string())],
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+    contract
+        .enable_commit_reveal(session_id, U64::from(100))
+        .unwrap();
+
+    let ids = vec![U16::from(1)];
+    let counts = vec![U64::from(4)];
+    let salt = B256::from([7u8; 32]);
+    vm.set_block_timestamp(100);
+    vm.set_sender(voter);
+    contract
+        .commit_vote(session_id, vote_commitment(session_id, voter, &ids, &counts, &[true], salt))
+        .unwrap();
+
+    // The direction is part of the commitment, so it cannot be flipped at reveal
+    vm.set_block_timestamp(250);
+    assert!(matches!(
+        contract.reveal_vote(session_id, ids.clone(), counts.clone(), Vec::new(), salt),
+        Err(QuadraticVotingError::InvalidCommitment(_))
+    ));
+    contract
+        .reveal_vote(session_id, ids, counts, vec![true], salt)
+        .unwrap();
+
+    let proposals = contract.get_session_proposals(session_id, U16::ZERO, U16::MAX).unwrap();
+    assert_eq!(
+        (proposals[0].3, proposals[0].4, proposals[0].5),
+        (U64::ZERO, U64::from(4), I128::try_from(-4).unwrap())
+    );
+}
+
+#[test]
+fn test_vote_by_sig_votes_against() {
+    use k256::ecdsa::SigningKey;
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let key = SigningKey::from_slice(&[0x42u8; 32]).unwrap();
+    let voter = signer::address_of(&key);
+    vm.set_sender(voter);
+    contract
+        .register_voter(identity_of(voter), Vec::new().into())
+        .unwrap();
+
+    vm.set_sender(admin);
+    let session_id = contract
+        .create_session(
+            "Test".to_string(),
+            "Desc".to_string(),
+            U256::from(100),
+            U64::ZERO,
+            U64::from(3600),
+            vec![
+                ("Proposal 1".to_string(), "Desc".to_string()),
+                ("Proposal 2".to_string(), "Desc".to_string()),
+            ],
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+
+    let ids = vec![U16::from(1), U16::from(2)];
+    let counts = vec![U64::from(3), U64::from(4)];
+    let against = vec![false, true];
+    let deadline = U256::from(vm.block_timestamp() + 600);
+    let digest = ballot_digest(
+        vm.chain_id(),
+        vm.contract_address(),
+        session_id,
+        &ids,
+        &counts,
+        &against,
+        voter,
+        U256::ZERO,
+        deadline,
+    );
+    let signature = signer::sign_digest(&key, digest);
+    mock_ecrecover(&vm, digest, &signature);
+
+    // A relayer cannot turn the signed opposition into support
+    let relayer = Address::from([9u8; 20]);
+    vm.set_sender(relayer);
+    assert!(matches!(
+        contract.vote_by_sig(
+            session_id,
+            ids.clone(),
+            counts.clone(),
+            Vec::new(),
+            voter,
+            U256::ZERO,
+            deadline,
+            signature.to_vec().into(),
+        ),
+        Err(QuadraticVotingError::InvalidSignature(_))
+    ));
+    contract
+        .vote_by_sig(
+            session_id,
+            ids,
+            counts,
+            against,
+            voter,
+            U256::ZERO,
+            deadline,
+            signature.to_vec().into(),
+        )
+        .unwrap();
+
+    let proposals = contract.get_session_proposals(session_id, U16::ZERO, U16::MAX).unwrap();
+    assert_eq!((proposals[0].3, proposals[0].4), (U64::from(3), U64::ZERO));
+    assert_eq!((proposals[1].3, proposals[1].4), (U64::ZERO, U64::from(4)));
+}
+
+#[test]
+fn test_submit_ballots_votes_against() {
+    use k256::ecdsa::SigningKey;
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let keys = [
+        SigningKey::from_slice(&[0x42u8; 32]).unwrap(),
+        SigningKey::from_slice(&[0x43u8; 32]).unwrap(),
+    ];
+    for key in &keys {
+        let voter = signer::address_of(key);
+        vm.set_sender(voter);
+        contract
+            .register_voter(identity_of(voter), Vec::new().into())
+            .unwrap();
+    }
+
+    vm.set_sender(admin);
+    let session_id = contract
+        .create_session(
+            "Test".to_string(),
+            "Desc".to_string(),
+            U256::from(100),
+            U64::ZERO,
+            U64::from(3600),
+            vec![("Proposal".to_string(), "Desc".to_string())],
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+
+    let deadline = U256::from(vm.block_timestamp() + 600);
+    let ballots = [(&keys[0], 5u64, false), (&keys[1], 2u64, true)]
+        .into_iter()
+        .map(|(key, count, opposes)| {
+            let voter = signer::address_of(key);
+            let (ids, counts, against) = (vec![U16::from(1)], vec![U64::from(count)], vec![opposes]);
+            let digest = ballot_digest(
+                vm.chain_id(),
+                vm.contract_address(),
+                session_id,
+                &ids,
+                &counts,
+                &against,
+                voter,
+                U256::ZERO,
+                deadline,
+            );
+            let signature = signer::sign_digest(key, digest);
+            mock_ecrecover(&vm, digest, &signature);
+            (session_id, ids, counts, against, voter, U256::ZERO, deadline, signature.to_vec().into())
+        })
+        .collect();
+
+    vm.set_sender(Address::from([9u8; 20]));
+    assert_eq!(contract.submit_ballots(ballots), vec![true, true]);
+    let proposals = contract.get_session_proposals(session_id, U16::ZERO, U16::MAX).unwrap();
+    assert_eq!(
+        (proposals[0].3, proposals[0].4, proposals[0].5),
+        (U64::from(5), U64::from(2), I128::try_from(3).unwrap())
+    );
+}
+
+#[test]
+fn test_vote_with_proof_votes_against() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let eligible: Vec<(Address, U256)> = (10u8..14)
+        .map(|b| (Address::from([b; 20]), U256::from(50)))
+        .collect();
+    let tree = MerkleTree::new(&eligible);
+    let session_id = contract
+        .create_session(
+            "Community".to_string(),
+            "Desc".to_string(),
+            U256::from(100),
+            U64::ZERO,
+            U64::from(3600),
+            vec![
+                ("Proposal 1".to_string(), "Desc 1".to_string()),
+                ("Proposal 2".to_string(), "Desc 2".to_string()),
+            ],
+            tree.root(),
+            U16::from(1),
+        )
+        .unwrap();
+
+    let (voter, allocation) = eligible[1];
+    vm.set_sender(voter);
+    contract
+        .vote_with_proof(
+            session_id,
+            vec![U16::from(1), U16::from(2)],
+            vec![U64::from(5), U64::from(4)],
+            vec![true, false],
+            allocation,
+            tree.proof(1).unwrap(),
+        )
+        .unwrap();
+
+    let proposals = contract.get_session_proposals(session_id, U16::ZERO, U16::MAX).unwrap();
+    assert_eq!((proposals[0].3, proposals[0].4), (U64::ZERO, U64::from(5)));
+    assert_eq!((proposals[1].3, proposals[1].4), (U64::from(4), U64::ZERO));
+    let (winner, _, max_net, _, _) = contract.get_session_results(session_id).unwrap();
+    assert_eq!((winner, max_net), (U16::from(2), U64::from(4)));
+}
+
+/// Multi-winner sessions
+#[test]
+fn test_top_k_winners() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let proposals: Vec<(String, String)> = (1..=5)
+        .map(|i| (format!("Proposal {i}"), "Desc".to_string()))
+        .collect();
+    assert!(matches!(
+        contract.create_session(
+            "Budget".to_string(),
+            "Desc".to_string(),
+            U256::from(100),
+            U64::ZERO,
+            U64::from(3600),
+            proposals.clone(),
+            B256::ZERO,
+            U16::ZERO,
+        ),
+        Err(QuadraticVotingError::InvalidProposalCount(_))
+    ));
+    let session_id = contract
+        .create_session(
+            "Budget".to_string(),
+            "Desc".to_string(),
+            U256::from(100),
+            U64::ZERO,
+            U64::from(3600),
+            proposals,
+            B256::ZERO,
+            U16::from(3),
+        )
+        .unwrap();
+    assert_eq!(contract.get_session(session_id).unwrap().8, U16::from(3));
+
+    let voters: Vec<Address> = (10u8..12).map(|b| Address::from([b; 20])).collect();
+    for &voter in &voters {
+        vm.set_sender(voter);
+        contract
+            .register_voter(identity_of(voter), Vec::new().into())
+            .unwrap();
+    }
+
+    // Net scores 5, 3, 3, 3, -1: three proposals compete for the last two seats
+    vm.set_sender(voters[0]);
+    contract
+        .vote(
+            session_id,
+            vec![U16::from(1), U16::from(2), U16::from(3), U16::from(4)],
+            vec![U64::from(5), U64::from(3), U64::from(3), U64::from(3)],
+        )
+        .unwrap();
+    vm.set_sender(voters[1]);
+    contract
+        .vote_with_intensities(session_id, vec![U16::from(5)], vec![I64::MINUS_ONE])
+        .unwrap();
+
+    assert!(matches!(
+        contract.get_winners(session_id),
+        Err(QuadraticVotingError::InvalidSession(_))
+    ));
+    vm.set_block_timestamp(vm.block_timestamp() + 3600);
+
+    // The default policy gives contested seats to the lowest IDs
+    assert_eq!(
+        contract.get_winners(session_id).unwrap(),
+        vec![U16::from(1), U16::from(2), U16::from(3)]
+    );
+    assert_eq!(contract.finalize_session(session_id).unwrap(), OUTCOME_PASSED);
+    let logs = vm.get_emitted_logs();
+    let (topics, data) = logs.last().unwrap();
+    assert_eq!(topics[0], WinnersSelected::SIGNATURE_HASH);
+    let event = WinnersSelected::decode_raw_log(topics.clone(), data, true).unwrap();
+    assert_eq!(event.proposal_ids, vec![1, 2, 3]);
+    assert_eq!(contract.get_session_results(session_id).unwrap().0, U16::from(1));
+}
+
+#[test]
+fn test_winners_tie_policies() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let voter = Address::from([10u8; 20]);
+    vm.set_sender(voter);
+    contract
+        .register_voter(identity_of(voter), Vec::new().into())
+        .unwrap();
+
+    let mut sessions = Vec::new();
+    for policy in [TIE_BREAK_NONE, TIE_BREAK_CREATOR, TIE_BREAK_EARLIEST] {
+        vm.set_sender(admin);
+        let session_id = contract
+            .create_session(
+                "Budget".to_string(),
+                "Desc".to_string(),
+                U256::from(100),
+                U64::ZERO,
+                U64::from(3600),
+                (1..=4)
+                    .map(|i| (format!("Proposal {i}"), "Desc".to_string()))
+                    .collect(),
+                B256::ZERO,
+                U16::from(2),
+            )
+            .unwrap();
+        contract.set_tie_break(session_id, policy).unwrap();
+
+        // Net scores 5, 3, 3, 0: proposal 3 reaches 3 before proposal 2
+        vm.set_sender(voter);
+        contract
+            .vote(session_id, vec![U16::from(1), U16::from(3)], vec![U64::from(5), U64::from(3)])
+            .unwrap();
+        contract
+            .vote(session_id, vec![U16::from(2)], vec![U64::from(3)])
+            .unwrap();
+        sessions.push(session_id);
+    }
+    vm.set_block_timestamp(vm.block_timestamp() + 3600);
+
+    // Without a tie-break the contested seat stays empty
+    assert_eq!(contract.get_winners(sessions[0]).unwrap(), vec![U16::from(1)]);
+    assert_eq!(contract.get_session_results(sessions[0]).unwrap().4, OUTCOME_PASSED);
+
+    // The creator fills the one contested seat
+    assert_eq!(contract.get_winners(sessions[1]).unwrap(), vec![U16::from(1)]);
+    vm.set_sender(admin);
+    assert!(matches!(
+        contract.resolve_tie(sessions[1], U16::from(1)),
+        Err(QuadraticVotingError::InvalidProposal(_))
+    ));
+    contract.resolve_tie(sessions[1], U16::from(3)).unwrap();
+    assert_eq!(contract.get_winners(sessions[1]).unwrap(), vec![U16::from(1), U16::from(3)]);
+
+    assert_eq!(contract.get_winners(sessions[2]).unwrap(), vec![U16::from(1), U16::from(3)]);
+
+    // Proposals below the threshold or without a positive net score never take a seat
+    let session_id = contract
+        .create_session(
+            "Budget".to_string(),
+            "Desc".to_string(),
+            U256::from(100),
+            U64::ZERO,
+            U64::from(3600),
+            (1..=3)
+                .map(|i| (format!("Proposal {i}"), "Desc".to_string()))
+                .collect(),
+            B256::ZERO,
+            U16::from(3),
+        )
+        .unwrap();
+    contract
+        .set_session_rules(session_id, U64::ZERO, U256::ZERO, U64::from(2))
+        .unwrap();
+    vm.set_sender(voter);
+    contract
+        .vote(session_id, vec![U16::from(1), U16::from(2)], vec![U64::from(4), U64::from(1)])
+        .unwrap();
+    vm.set_block_timestamp(vm.block_timestamp() + 3600);
+    assert_eq!(contract.get_winners(session_id).unwrap(), vec![U16::from(1)]);
+}
+
+/// Budget allocation
+
+#[test]
+fn test_budget_allocation_funds_greedily() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let voters: Vec<Address> = (10u8..12).map(|b| Address::from([b; 20])).collect();
+    for &voter in &voters {
+        vm.set_sender(voter);
+        contract
+            .register_voter(identity_of(voter), Vec::new().into())
+            .unwrap();
+    }
+
+    let now = vm.block_timestamp();
+    vm.set_sender(admin);
+    let proposals: Vec<(String, String)> = (1..=4)
+        .map(|i| (format!("Grant {i}"), "Desc".to_string()))
+        .collect();
+    let session_id = contract
+        .create_session(
+            "Grants".to_string(),
+            "Desc".to_string(),
+            U256::from(100),
+            U64::from(now + 10),
+            U64::from(3600),
+            proposals,
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+    contract
+        .enable_proposal_submissions(session_id, U16::from(2), U256::ZERO)
+        .unwrap();
+
+    let amounts: Vec<U256> = [60u64, 50, 30, 20].iter().map(|&a| U256::from(a)).collect();
+    assert!(matches!(
+        contract.enable_budget_mode(session_id, U256::from(100), amounts[..3].to_vec()),
+        Err(QuadraticVotingError::InvalidProposalCount(_))
+    ));
+    assert!(matches!(
+        contract.enable_budget_mode(session_id, U256::ZERO, amounts.clone()),
+        Err(QuadraticVotingError::InvalidAmount(_))
+    ));
+    assert!(matches!(
+        contract.enable_budget_mode(
+            session_id,
+            U256::from(100),
+            vec![U256::from(60), U256::ZERO, U256::from(30), U256::from(20)]
+        ),
+        Err(QuadraticVotingError::InvalidAmount(_))
+    ));
+    vm.set_sender(voters[0]);
+    assert!(matches!(
+        contract.enable_budget_mode(session_id, U256::from(100), amounts.clone()),
+        Err(QuadraticVotingError::Unauthorized(_))
+    ));
+    vm.set_sender(admin);
+    contract
+        .enable_budget_mode(session_id, U256::from(100), amounts)
+        .unwrap();
+    assert_eq!(contract.get_requested_amount(session_id, U16::from(2)), U256::from(50));
+
+    // Budget mode closes proposal submissions
+    vm.set_sender(voters[0]);
+    assert!(matches!(
+        contract.submit_proposal(session_id, "Late".to_string(), "Desc".to_string()),
+        Err(QuadraticVotingError::InvalidSession(_))
+    ));
+
+    // Net scores 5, 4, 3, -1 against requests of 60, 50, 30, 20
+    vm.set_block_timestamp(now + 10);
+    vm.set_sender(admin);
+    assert!(matches!(
+        contract.enable_budget_mode(session_id, U256::from(100), vec![U256::from(1); 4]),
+        Err(QuadraticVotingError::InvalidSession(_))
+    ));
+    vm.set_sender(voters[0]);
+    contract
+        .vote(
+            session_id,
+            vec![U16::from(1), U16::from(2), U16::from(3)],
+            vec![U64::from(5), U64::from(4), U64::from(3)],
+        )
+        .unwrap();
+    vm.set_sender(voters[1]);
+    contract
+        .vote_with_intensities(session_id, vec![U16::from(4)], vec![I64::MINUS_ONE])
+        .unwrap();
+
+    // Proposal 2 no longer fits after proposal 1, so the cheaper proposal 3 is funded instead
+    assert_eq!(
+        contract.get_budget_allocation(session_id).unwrap(),
+        (false, vec![U16::from(1), U16::from(3)], U256::from(10))
+    );
+
+    vm.set_block_timestamp(now + 10 + 3600);
+    contract.finalize_session(session_id).unwrap();
+    let logs = vm.get_emitted_logs();
+    let (topics, data) = logs.last().unwrap();
+    assert_eq!(topics[0], BudgetAllocated::SIGNATURE_HASH);
+    let event = BudgetAllocated::decode_raw_log(topics.clone(), data, true).unwrap();
+    assert_eq!(event.funded_ids, vec![1, 3]);
+    assert_eq!(event.remaining, U256::from(10));
+    assert_eq!(
+        contract.get_budget_allocation(session_id).unwrap(),
+        (true, vec![U16::from(1), U16::from(3)], U256::from(10))
+    );
+}
+
+#[test]
+fn test_budget_allocation_tie_policies() {
+    use stylus_sdk::testing::*;
+    for policy in [TIE_BREAK_LOWEST_ID, TIE_BREAK_EARLIEST, TIE_BREAK_CREATOR, TIE_BREAK_NONE] {
+        let vm = TestVM::default();
+        let admin = Address::from([1u8; 20]);
+        vm.set_sender(admin);
+        let mut contract = QuadraticVoting::from(&vm);
+        contract.constructor();
+
+        let voters: Vec<Address> = (10u8..12).map(|b| Address::from([b; 20])).collect();
+        for &voter in &voters {
+            vm.set_sender(voter);
+            contract
+                .register_voter(identity_of(voter), Vec::new().into())
+                .unwrap();
+        }
+
+        let now = vm.block_timestamp();
+        vm.set_sender(admin);
+        let proposals: Vec<(String, String)> = (1..=3)
+            .map(|i| (format!("Grant {i}"), "Desc".to_string()))
+            .collect();
+        let session_id = contract
+            .create_session(
+                "Grants".to_string(),
+                "Desc".to_string(),
+                U256::from(100),
+                U64::from(now + 10),
+                U64::from(3600),
+                proposals,
+                B256::ZERO,
+                U16::from(3),
+            )
+            .unwrap();
+        contract
+            .enable_budget_mode(
+                session_id,
+                U256::from(100),
+                vec![U256::from(60), U256::from(60), U256::from(30)],
+            )
+            .unwrap();
+        contract.set_tie_break(session_id, policy).unwrap();
+
+        // Proposals 1 and 2 tie at 4 and cannot both be funded; proposal 2 reaches the score first
+        vm.set_block_timestamp(now + 10);
+        vm.set_sender(voters[0]);
+        contract
+            .vote(session_id, vec![U16::from(2), U16::from(3)], vec![U64::from(4), U64::from(2)])
+            .unwrap();
+        vm.set_block_timestamp(now + 20);
+        vm.set_sender(voters[1]);
+        contract
+            .vote(session_id, vec![U16::from(1)], vec![U64::from(4)])
+            .unwrap();
+
+        let preview = contract.get_budget_allocation(session_id).unwrap();
+        match policy {
+            TIE_BREAK_LOWEST_ID => assert_eq!(preview, (false, vec![U16::from(1), U16::from(3)], U256::from(10))),
+            TIE_BREAK_EARLIEST => assert_eq!(preview, (false, vec![U16::from(2), U16::from(3)], U256::from(10))),
+            // An unsettled tie stops funding before the tied proposals or anything below them
+            _ => assert_eq!(preview, (false, Vec::new(), U256::from(100))),
+        }
+
+        vm.set_block_timestamp(now + 10 + 3600);
+        if policy == TIE_BREAK_CREATOR {
+            // Only the creator can finalize while the budget tie is open
+            vm.set_sender(voters[0]);
+            assert!(matches!(
+                contract.finalize_session(session_id),
+                Err(QuadraticVotingError::Unauthorized(_))
+            ));
+            vm.set_sender(admin);
+            assert!(matches!(
+                contract.resolve_tie(session_id, U16::from(3)),
+                Err(QuadraticVotingError::InvalidProposal(_))
+            ));
+            contract.resolve_tie(session_id, U16::from(2)).unwrap();
+            assert_eq!(
+                contract.get_budget_allocation(session_id).unwrap(),
+                (false, vec![U16::from(2), U16::from(3)], U256::from(10))
+            );
+            vm.set_sender(voters[0]);
+        }
+        if policy == TIE_BREAK_NONE {
+            vm.set_sender(admin);
+            assert!(matches!(
+                contract.resolve_tie(session_id, U16::from(1)),
+                Err(QuadraticVotingError::InvalidSession(_))
+            ));
+        }
+
+        contract.finalize_session(session_id).unwrap();
+        let (_, funded, remaining) = contract.get_budget_allocation(session_id).unwrap();
+        match policy {
+            TIE_BREAK_LOWEST_ID => assert_eq!(funded, vec![U16::from(1), U16::from(3)]),
+            TIE_BREAK_NONE => {
+                assert!(funded.is_empty());
+                assert_eq!(remaining, U256::from(100));
+            }
+            _ => assert_eq!(funded, vec![U16::from(2), U16::from(3)]),
+        }
+    }
+}
+
+#[test]
+fn test_budget_allocation_respects_quorum() {
+    use stylus_sdk::testing::*;
+    let vm = TestVM::default();
+    let admin = Address::from([1u8; 20]);
+    vm.set_sender(admin);
+    let mut contract = QuadraticVoting::from(&vm);
+    contract.constructor();
+
+    let voter = Address::from([10u8; 20]);
+    vm.set_sender(voter);
+    contract
+        .register_voter(identity_of(voter), Vec::new().into())
+        .unwrap();
+
+    let now = vm.block_timestamp();
+    vm.set_sender(admin);
+    let session_id = contract
+        .create_session(
+            "Grants".to_string(),
+            "Desc".to_string(),
+            U256::from(100),
+            U64::from(now + 10),
+            U64::from(3600),
+            vec![
+                ("Grant 1".to_string(), "Desc".to_string()),
+                ("Grant 2".to_string(), "Desc".to_string()),
+            ],
+            B256::ZERO,
+            U16::from(1),
+        )
+        .unwrap();
+    assert!(matches!(
+        contract.get_budget_allocation(session_id),
+        Err(QuadraticVotingError::InvalidSession(_))
+    ));
+    contract
+        .enable_budget_mode(session_id, U256::from(100), vec![U256::from(40); 2])
+        .unwrap();
+    contract
+        .set_session_rules(session_id, U64::from(2), U256::ZERO, U64::ZERO)
+        .unwrap();
+
+    vm.set_block_timestamp(now + 10);
+    vm.set_sender(voter);
+    contract
+        .vote(session_id, vec![U16::from(1), U16::from(2)], vec![U64::from(3), U64::from(2)])
+        .unwrap();
+
+    // A single voter misses the quorum of two, so the whole budget stays unspent
+    vm.set_block_timestamp(now + 10 + 3600);
+    assert_eq!(contract.finalize_session(session_id).unwrap(), OUTCOME_FAILED_QUORUM);
+    assert_eq!(
+        contract.get_budget_allocation(session_id).unwrap(),
+        (true, Vec::new(), U256::from(100))
+    );
+}